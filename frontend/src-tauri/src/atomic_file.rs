@@ -0,0 +1,178 @@
+use crate::error::AppError;
+use serde::de::IgnoredAny;
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+fn sibling_with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling_with_suffix(path, ".bak")
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    sibling_with_suffix(path, ".tmp")
+}
+
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Writes `contents` to a sibling temp file, fsyncs it and renames it over `path`, so readers
+/// only ever see the old or the new file, never a truncated one.
+fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = temp_path(path);
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&tmp, path)?;
+        sync_parent_dir(path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+/// Keeps the current file as `<name>.bak` before it is replaced, but only while it still holds
+/// valid JSON so a corrupt primary never overwrites the last good backup.
+fn refresh_backup(path: &Path) -> io::Result<()> {
+    let current = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if serde_json::from_slice::<IgnoredAny>(&current).is_err() {
+        return Ok(());
+    }
+    replace_file(&backup_path(path), &current)
+}
+
+pub fn write_json_atomic<T: Serialize>(path: &Path, value: &T, label: &str) -> Result<(), AppError> {
+    let raw = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::internal(format!("serialize {label} error: {e}")).with_path(path))?;
+    refresh_backup(path).map_err(|e| AppError::io(path, format!("backup {label} error: {e}")))?;
+    replace_file(path, raw.as_bytes()).map_err(|e| AppError::io(path, format!("write {label} error: {e}")))
+}
+
+/// Reads `path` with `parse`, falling back to the `.bak` copy when the primary file is missing
+/// or cannot be parsed. Returns `Ok(None)` when neither file exists. Errors carry `path`.
+pub fn read_with_backup<T>(
+    path: &Path,
+    label: &str,
+    parse: impl Fn(&str) -> Result<T, AppError>,
+) -> Result<Option<T>, AppError> {
+    let backup = backup_path(path);
+    let primary_error = match fs::read_to_string(path) {
+        Ok(raw) => match parse(&raw) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => e.or_path(path),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            if !backup.exists() {
+                return Ok(None);
+            }
+            AppError::io(path, format!("{label} file is missing"))
+        }
+        Err(e) => AppError::io(path, format!("read {label} error: {e}")),
+    };
+
+    let Ok(raw) = fs::read_to_string(&backup) else {
+        return Err(primary_error);
+    };
+    match parse(&raw) {
+        Ok(value) => Ok(Some(value)),
+        Err(_) => Err(primary_error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use serde_json::{json, Value};
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("atomic-file-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn read(path: &Path) -> Result<Option<Value>, AppError> {
+        read_with_backup(path, "test", |raw| {
+            serde_json::from_str(raw).map_err(|e| AppError::corrupt(format!("parse test error: {e}")))
+        })
+    }
+
+    #[test]
+    fn a_write_replaces_the_file_and_keeps_the_previous_one_as_backup() {
+        let folder = temp_folder();
+        let path = folder.join("data.json");
+        write_json_atomic(&path, &json!({ "n": 1 }), "test").unwrap();
+        write_json_atomic(&path, &json!({ "n": 2 }), "test").unwrap();
+
+        assert_eq!(read(&path).unwrap(), Some(json!({ "n": 2 })));
+        let backup: Value = serde_json::from_str(&fs::read_to_string(backup_path(&path)).unwrap()).unwrap();
+        assert_eq!(backup, json!({ "n": 1 }));
+        assert!(!temp_path(&path).exists());
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn a_corrupt_primary_falls_back_to_the_backup() {
+        let folder = temp_folder();
+        let path = folder.join("data.json");
+        fs::write(&path, "{ \"n\": ").unwrap();
+        fs::write(backup_path(&path), "{ \"n\": 1 }").unwrap();
+
+        assert_eq!(read(&path).unwrap(), Some(json!({ "n": 1 })));
+        fs::write(backup_path(&path), "also broken").unwrap();
+        let error = read(&path).unwrap_err();
+        assert_eq!(error.code, ErrorCode::CorruptData);
+        assert_eq!(error.path.as_deref(), Some(path.to_string_lossy().as_ref()));
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn a_missing_primary_falls_back_to_the_backup() {
+        let folder = temp_folder();
+        let path = folder.join("data.json");
+        assert_eq!(read(&path).unwrap(), None);
+
+        fs::write(backup_path(&path), "{ \"n\": 1 }").unwrap();
+        assert_eq!(read(&path).unwrap(), Some(json!({ "n": 1 })));
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn an_invalid_primary_never_replaces_a_good_backup() {
+        let folder = temp_folder();
+        let path = folder.join("data.json");
+        fs::write(&path, "not json").unwrap();
+        fs::write(backup_path(&path), "{ \"n\": 1 }").unwrap();
+
+        refresh_backup(&path).unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "{ \"n\": 1 }");
+        write_json_atomic(&path, &json!({ "n": 2 }), "test").unwrap();
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), "{ \"n\": 1 }");
+        assert_eq!(read(&path).unwrap(), Some(json!({ "n": 2 })));
+        let _ = fs::remove_dir_all(&folder);
+    }
+}
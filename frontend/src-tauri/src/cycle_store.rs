@@ -0,0 +1,434 @@
+use crate::atomic_file::write_json_atomic;
+use crate::error::{AppError, ErrorCode};
+use crate::models::{CycleData, StorageKind};
+use crate::schema::{ensure_not_newer, migrate_cycle_value, newer_version_error, CYCLE_SCHEMA_VERSION};
+use crate::{cycle_file_path, read_cycle_file};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+pub const SQLITE_FILE: &str = "cycle_data.sqlite";
+
+/// Reads and writes the data of one cycle folder. Every implementation must round-trip a
+/// `CycleData` exactly, including fields it does not know about.
+pub trait CycleStore: Sync {
+    /// The file that changes with every save; revisions and change detection hash it.
+    fn data_file(&self, folder: &Path) -> PathBuf;
+    /// `None` when nothing was saved in `folder` yet.
+    fn read(&self, folder: &Path) -> Result<Option<CycleData>, AppError>;
+    fn write(&self, folder: &Path, data: &CycleData) -> Result<(), AppError>;
+}
+
+/// Pretty-printed `cycle_data.json`, rewritten atomically on every save.
+pub struct JsonStore;
+
+/// `cycle_data.sqlite` with one row per entity, so a save only touches the rows that changed.
+pub struct SqliteStore;
+
+pub fn store_for(kind: StorageKind) -> &'static dyn CycleStore {
+    match kind {
+        StorageKind::Json => &JsonStore,
+        StorageKind::Sqlite => &SqliteStore,
+    }
+}
+
+/// Storage used by the data found in `folder`, preferring JSON when both files exist.
+pub fn detect_storage(folder: &Path) -> Option<StorageKind> {
+    [StorageKind::Json, StorageKind::Sqlite]
+        .into_iter()
+        .find(|kind| store_for(*kind).data_file(folder).is_file())
+}
+
+impl CycleStore for JsonStore {
+    fn data_file(&self, folder: &Path) -> PathBuf {
+        cycle_file_path(&folder.to_string_lossy())
+    }
+
+    fn read(&self, folder: &Path) -> Result<Option<CycleData>, AppError> {
+        read_cycle_file(&self.data_file(folder))
+    }
+
+    fn write(&self, folder: &Path, data: &CycleData) -> Result<(), AppError> {
+        let file = self.data_file(folder);
+        ensure_not_newer(&file, "cycle data", CYCLE_SCHEMA_VERSION)?;
+        write_json_atomic(&file, data, "cycle data")
+    }
+}
+
+/// Each entity list of `CycleData` lives in the table of the same name.
+const ENTITY_TABLES: [&str; 3] = ["goals", "works", "tasks"];
+
+/// Gap between the positions of neighbouring rows when they are (re)numbered. Rows inserted or
+/// moved later take a position between their neighbours, so other rows keep theirs.
+const POSITION_STEP: f64 = 1024.0;
+
+/// A locked or unwritable database file is the usual cause, so these are reported as I/O errors
+/// the user can retry.
+fn sqlite_error(e: rusqlite::Error) -> AppError {
+    AppError::new(ErrorCode::Io, format!("cycle database error: {e}"))
+}
+
+/// Opens the database for reading without creating or changing anything in it.
+fn open_read_only(file: &Path) -> Result<Connection, AppError> {
+    Connection::open_with_flags(file, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(sqlite_error)
+}
+
+/// Opens the database for a save, creating the tables the first time. Ids are not unique keys:
+/// a damaged cycle can repeat one, and it must still round-trip until `repair_cycle` fixes it.
+fn open_for_write(file: &Path) -> Result<Connection, AppError> {
+    let conn = Connection::open(file).map_err(sqlite_error)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS cycle (
+             id INTEGER PRIMARY KEY CHECK (id = 1),
+             schema_version INTEGER NOT NULL,
+             document TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS goals (id TEXT NOT NULL, position REAL NOT NULL, body TEXT NOT NULL);
+         CREATE TABLE IF NOT EXISTS works (id TEXT NOT NULL, position REAL NOT NULL, body TEXT NOT NULL);
+         CREATE TABLE IF NOT EXISTS tasks (id TEXT NOT NULL, position REAL NOT NULL, body TEXT NOT NULL);
+         CREATE INDEX IF NOT EXISTS goals_position ON goals (position);
+         CREATE INDEX IF NOT EXISTS works_position ON works (position);
+         CREATE INDEX IF NOT EXISTS tasks_position ON tasks (position);",
+    )
+    .map_err(sqlite_error)?;
+    Ok(conn)
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, AppError> {
+    serde_json::to_string(value).map_err(|e| AppError::internal(format!("serialize cycle data error: {e}")))
+}
+
+fn from_json<T: DeserializeOwned>(raw: &str) -> Result<T, AppError> {
+    serde_json::from_str(raw).map_err(|e| AppError::corrupt(format!("parse cycle data error: {e}")))
+}
+
+fn read_rows(conn: &Connection, table: &str) -> Result<Vec<Value>, AppError> {
+    let mut statement = conn
+        .prepare(&format!("SELECT body FROM {table} ORDER BY position"))
+        .map_err(sqlite_error)?;
+    let rows = statement
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(sqlite_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(sqlite_error)?;
+    rows.iter().map(|raw| from_json(raw)).collect()
+}
+
+/// A stored entity row; `body` stays serialized since saves only compare it.
+struct StoredRow {
+    rowid: i64,
+    id: String,
+    position: f64,
+    body: String,
+}
+
+fn stored_rows(conn: &Connection, table: &str) -> Result<Vec<StoredRow>, AppError> {
+    let mut statement = conn
+        .prepare(&format!("SELECT rowid, id, position, body FROM {table} ORDER BY position"))
+        .map_err(sqlite_error)?;
+    statement
+        .query_map([], |row| {
+            Ok(StoredRow {
+                rowid: row.get(0)?,
+                id: row.get(1)?,
+                position: row.get(2)?,
+                body: row.get(3)?,
+            })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(sqlite_error)
+}
+
+/// Indexes into `values` of the longest strictly increasing subsequence of the present values.
+fn longest_increasing(values: &[Option<f64>]) -> Vec<usize> {
+    // tails[k]: index of the smallest value ending an increasing run of length k + 1.
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; values.len()];
+    for (index, value) in values.iter().enumerate() {
+        let Some(value) = *value else {
+            continue;
+        };
+        let length = tails.partition_point(|&tail| values[tail].is_some_and(|t| t < value));
+        previous[index] = length.checked_sub(1).map(|k| tails[k]);
+        if length == tails.len() {
+            tails.push(index);
+        } else {
+            tails[length] = index;
+        }
+    }
+    let mut kept = vec![];
+    let mut next = tails.last().copied();
+    while let Some(index) = next {
+        kept.push(index);
+        next = previous[index];
+    }
+    kept.reverse();
+    kept
+}
+
+/// Positions for `count` new slots between `low` and `high` (either may be open), or `None` when
+/// the gap is too small to split further.
+fn positions_between(low: Option<f64>, high: Option<f64>, count: usize) -> Option<Vec<f64>> {
+    let positions = (1..=count)
+        .map(|n| match (low, high) {
+            (Some(low), Some(high)) => low + (high - low) * n as f64 / (count + 1) as f64,
+            (Some(low), None) => low + POSITION_STEP * n as f64,
+            (None, Some(high)) => high - POSITION_STEP * (count + 1 - n) as f64,
+            (None, None) => POSITION_STEP * n as f64,
+        })
+        .collect::<Vec<_>>();
+    let bounds = low.into_iter().chain(positions.iter().copied()).chain(high);
+    let increasing = bounds.clone().zip(bounds.skip(1)).all(|(a, b)| a < b);
+    increasing.then_some(positions)
+}
+
+/// Target position of every item: rows that are still in order keep theirs, the others (new or
+/// moved) get one between their neighbours. Everything is renumbered once a gap runs out.
+fn plan_positions(current: &[Option<f64>]) -> Vec<f64> {
+    let mut planned = vec![None; current.len()];
+    for index in longest_increasing(current) {
+        planned[index] = current[index];
+    }
+    let mut start = 0;
+    while start < planned.len() {
+        if planned[start].is_some() {
+            start += 1;
+            continue;
+        }
+        let end = (start..planned.len()).find(|&i| planned[i].is_some()).unwrap_or(planned.len());
+        let low = start.checked_sub(1).and_then(|i| planned[i]);
+        let high = planned.get(end).copied().flatten();
+        let Some(positions) = positions_between(low, high, end - start) else {
+            return (1..=current.len()).map(|n| POSITION_STEP * n as f64).collect();
+        };
+        for (slot, position) in planned[start..end].iter_mut().zip(positions) {
+            *slot = Some(position);
+        }
+        start = end;
+    }
+    planned.into_iter().flatten().collect()
+}
+
+/// Saves `items` into `table`, matching them to the stored rows by id. Only rows whose entity
+/// changed, moved, appeared or disappeared are written.
+fn write_rows<T: Serialize>(conn: &Connection, table: &str, items: &[T]) -> Result<(), AppError> {
+    let mut stored = HashMap::<String, VecDeque<StoredRow>>::new();
+    for row in stored_rows(conn, table)? {
+        stored.entry(row.id.clone()).or_default().push_back(row);
+    }
+
+    let mut matched = vec![];
+    for item in items {
+        let value = serde_json::to_value(item)
+            .map_err(|e| AppError::internal(format!("serialize cycle data error: {e}")))?;
+        let id = value.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+        let row = stored.get_mut(&id).and_then(VecDeque::pop_front);
+        matched.push((id, to_json(&value)?, row));
+    }
+    let current = matched.iter().map(|(_, _, row)| row.as_ref().map(|r| r.position)).collect::<Vec<_>>();
+    let positions = plan_positions(&current);
+
+    for row in stored.into_values().flatten() {
+        conn.execute(&format!("DELETE FROM {table} WHERE rowid = ?1"), params![row.rowid])
+            .map_err(sqlite_error)?;
+    }
+    for ((id, body, row), position) in matched.into_iter().zip(positions) {
+        match row {
+            Some(row) if row.body == body && row.position == position => {}
+            Some(row) => {
+                conn.execute(
+                    &format!("UPDATE {table} SET position = ?1, body = ?2 WHERE rowid = ?3"),
+                    params![position, body, row.rowid],
+                )
+                .map_err(sqlite_error)?;
+            }
+            None => {
+                conn.execute(
+                    &format!("INSERT INTO {table} (id, position, body) VALUES (?1, ?2, ?3)"),
+                    params![id, position, body],
+                )
+                .map_err(sqlite_error)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl CycleStore for SqliteStore {
+    fn data_file(&self, folder: &Path) -> PathBuf {
+        folder.join(SQLITE_FILE)
+    }
+
+    fn read(&self, folder: &Path) -> Result<Option<CycleData>, AppError> {
+        let file = self.data_file(folder);
+        if !file.is_file() {
+            return Ok(None);
+        }
+        read_database(&file).map_err(|e| e.or_path(&file))
+    }
+
+    fn write(&self, folder: &Path, data: &CycleData) -> Result<(), AppError> {
+        let file = self.data_file(folder);
+        write_database(&file, data).map_err(|e| e.or_path(&file))
+    }
+}
+
+fn read_database(file: &Path) -> Result<Option<CycleData>, AppError> {
+    let conn = open_read_only(file)?;
+    let has_cycle = conn
+        .query_row("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'cycle'", [], |_| Ok(()))
+        .optional()
+        .map_err(sqlite_error)?
+        .is_some();
+    if !has_cycle {
+        return Ok(None);
+    }
+    let document = conn
+        .query_row("SELECT document FROM cycle WHERE id = 1", [], |row| row.get::<_, String>(0))
+        .optional()
+        .map_err(sqlite_error)?;
+    let Some(document) = document else {
+        return Ok(None);
+    };
+
+    let mut doc = from_json::<Map<String, Value>>(&document)?;
+    for table in ENTITY_TABLES {
+        doc.insert(table.to_string(), Value::Array(read_rows(&conn, table)?));
+    }
+    let data = migrate_cycle_value(Value::Object(doc))?;
+    serde_json::from_value(data)
+        .map(Some)
+        .map_err(|e| AppError::corrupt(format!("parse cycle data error: {e}")))
+}
+
+fn write_database(file: &Path, data: &CycleData) -> Result<(), AppError> {
+    let mut conn = open_for_write(file)?;
+    let stored_version = conn
+        .query_row("SELECT schema_version FROM cycle WHERE id = 1", [], |row| row.get::<_, u32>(0))
+        .optional()
+        .map_err(sqlite_error)?;
+    if let Some(found) = stored_version.filter(|v| *v > CYCLE_SCHEMA_VERSION) {
+        return Err(newer_version_error("cycle data", found, CYCLE_SCHEMA_VERSION));
+    }
+
+    let mut document = match serde_json::to_value(data) {
+        Ok(Value::Object(doc)) => doc,
+        Ok(_) => return Err(AppError::internal("serialize cycle data error: expected a JSON object")),
+        Err(e) => return Err(AppError::internal(format!("serialize cycle data error: {e}"))),
+    };
+    for table in ENTITY_TABLES {
+        document.remove(table);
+    }
+
+    let tx = conn.transaction().map_err(sqlite_error)?;
+    tx.execute(
+        "INSERT OR REPLACE INTO cycle (id, schema_version, document) VALUES (1, ?1, ?2)",
+        params![data.schema_version, to_json(&document)?],
+    )
+    .map_err(sqlite_error)?;
+    write_rows(&tx, "goals", &data.goals)?;
+    write_rows(&tx, "works", &data.works)?;
+    write_rows(&tx, "tasks", &data.tasks)?;
+    tx.commit().map_err(sqlite_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cycle(tasks: &[(&str, &str)]) -> CycleData {
+        let tasks = tasks
+            .iter()
+            .map(|(id, title)| json!({ "id": id, "cycleId": "c1", "workId": "w1", "title": title }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "schemaVersion": CYCLE_SCHEMA_VERSION,
+            "id": "c1",
+            "name": "Cycle",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("cycle-store-test-{:x}", rand::random::<u64>()));
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn positions(folder: &Path) -> Vec<(String, f64)> {
+        let conn = open_read_only(&SqliteStore.data_file(folder)).unwrap();
+        stored_rows(&conn, "tasks").unwrap().into_iter().map(|row| (row.id, row.position)).collect()
+    }
+
+    #[test]
+    fn rows_in_order_keep_their_positions() {
+        assert_eq!(plan_positions(&[Some(1.0), None, Some(2.0), None]), [1.0, 1.5, 2.0, 2.0 + POSITION_STEP]);
+        assert_eq!(plan_positions(&[None, None]), [POSITION_STEP, 2.0 * POSITION_STEP]);
+    }
+
+    #[test]
+    fn only_the_moved_row_gets_a_new_position() {
+        assert_eq!(plan_positions(&[Some(3.0), Some(1.0), Some(2.0)]), [1.0 - POSITION_STEP, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn a_gap_that_cannot_be_split_renumbers_everything() {
+        let low = 1.0_f64;
+        let high = f64::from_bits(low.to_bits() + 1);
+        assert_eq!(plan_positions(&[Some(low), None, Some(high)]), [1.0, 2.0, 3.0].map(|n| n * POSITION_STEP));
+    }
+
+    #[test]
+    fn sqlite_round_trips_and_only_touches_changed_rows() {
+        let folder = temp_folder();
+        let first = cycle(&[("t1", "Write"), ("t2", "Test"), ("t3", "Ship")]);
+        SqliteStore.write(&folder, &first).unwrap();
+        let before = positions(&folder);
+
+        let second = cycle(&[("t1", "Write"), ("t4", "Review"), ("t2", "Test"), ("t3", "Ship it")]);
+        SqliteStore.write(&folder, &second).unwrap();
+        let after = positions(&folder);
+        let read = SqliteStore.read(&folder).unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&folder);
+
+        assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&second).unwrap());
+        for id in ["t1", "t2", "t3"] {
+            let position = |rows: &[(String, f64)]| rows.iter().find(|(row, _)| row == id).map(|(_, p)| *p);
+            assert_eq!(position(&before), position(&after), "{id} moved");
+        }
+    }
+
+    #[test]
+    fn repeated_ids_round_trip() {
+        let folder = temp_folder();
+        let data = cycle(&[("t1", "Write"), ("t1", "Write again")]);
+        SqliteStore.write(&folder, &data).unwrap();
+        SqliteStore.write(&folder, &cycle(&[("t1", "Write again"), ("t1", "Write")])).unwrap();
+        let read = SqliteStore.read(&folder).unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&folder);
+
+        let titles = read.tasks.iter().map(|t| t.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, ["Write again", "Write"]);
+    }
+
+    #[test]
+    fn reading_an_empty_database_creates_nothing() {
+        let folder = temp_folder();
+        let file = SqliteStore.data_file(&folder);
+        std::fs::write(&file, b"").unwrap();
+        assert!(SqliteStore.read(&folder).unwrap().is_none());
+        let tables = Connection::open(&file)
+            .unwrap()
+            .query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&folder);
+        assert_eq!(tables, 0);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::{AppError, ErrorCode};
+use serde::Serialize;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the folder that holds the index and app settings.
+pub const DATA_DIR_ENV: &str = "CYCLE_PLANNER_DATA_DIR";
+/// Command line flag with the same meaning, as `--data-dir <folder>` or `--data-dir=<folder>`.
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+/// When this file sits next to the executable, the app runs portable and keeps its data in
+/// `PORTABLE_DATA_DIR` beside it.
+pub const PORTABLE_FLAG_FILE: &str = "portable.flag";
+pub const PORTABLE_DATA_DIR: &str = "data";
+
+/// Entries of the data folder that move with it. `index.json` comes last: once it exists the
+/// migration counts as done.
+const DATA_ENTRIES: [&str; 7] = [
+    "sync_bases",
+    "window_state.json.bak",
+    "window_state.json",
+    "settings.json.bak",
+    "settings.json",
+    "index.json.bak",
+    "index.json",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataDirSource {
+    CommandLine,
+    Environment,
+    Portable,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDir {
+    pub path: PathBuf,
+    pub source: DataDirSource,
+    /// The per-user folder the app uses without an override, where an existing index usually is.
+    pub default_path: PathBuf,
+}
+
+fn flag_value(args: &[OsString]) -> Option<PathBuf> {
+    let prefix = format!("{DATA_DIR_FLAG}=");
+    args.iter().enumerate().find_map(|(i, arg)| {
+        let arg = arg.to_str()?;
+        if arg == DATA_DIR_FLAG {
+            return args.get(i + 1).map(PathBuf::from);
+        }
+        arg.strip_prefix(&prefix).map(PathBuf::from)
+    })
+}
+
+fn portable_dir(exe_dir: &Path) -> Option<PathBuf> {
+    exe_dir
+        .join(PORTABLE_FLAG_FILE)
+        .is_file()
+        .then(|| exe_dir.join(PORTABLE_DATA_DIR))
+}
+
+/// Picks the data folder: the command line flag wins over the environment variable, which wins
+/// over portable mode; otherwise `default_path` is used.
+pub fn resolve(default_path: PathBuf) -> DataDir {
+    let args = std::env::args_os().skip(1).collect::<Vec<_>>();
+    let exe_dir = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf));
+    resolve_from(&args, std::env::var_os(DATA_DIR_ENV), exe_dir.as_deref(), default_path)
+}
+
+/// `resolve` over the command line arguments, the value of `DATA_DIR_ENV` and the folder of the
+/// executable.
+fn resolve_from(args: &[OsString], env: Option<OsString>, exe_dir: Option<&Path>, default_path: PathBuf) -> DataDir {
+    let (path, source) = if let Some(path) = flag_value(args) {
+        (path, DataDirSource::CommandLine)
+    } else if let Some(path) = env.filter(|v| !v.is_empty()) {
+        (PathBuf::from(path), DataDirSource::Environment)
+    } else if let Some(path) = exe_dir.and_then(portable_dir) {
+        (path, DataDirSource::Portable)
+    } else {
+        (default_path.clone(), DataDirSource::Default)
+    };
+    DataDir {
+        path,
+        source,
+        default_path,
+    }
+}
+
+/// Copies `from` into `to`, keeping the files `to` already has.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)?.flatten() {
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if !target.exists() {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `to` already has its own `name`, or the file `name` is the backup of.
+fn kept_in_target(to: &Path, name: &str) -> bool {
+    to.join(name).exists() || name.strip_suffix(".bak").is_some_and(|primary| to.join(primary).exists())
+}
+
+/// Copies the index, settings and merge bases from `from` into `to`. The source is left in place
+/// so the previous location keeps working. A folder that already has an index is refused, and
+/// anything else `to` already has (settings or window state saved since the app started using
+/// it) is kept rather than overwritten.
+pub fn migrate(from: &Path, to: &Path) -> Result<(), AppError> {
+    if from == to {
+        return Err(AppError::invalid_input("The index is already stored in this folder.").with_path(to));
+    }
+    if !from.join("index.json").is_file() {
+        return Err(AppError::new(ErrorCode::NotFound, "There is no index to migrate in this folder.").with_path(from));
+    }
+    if to.join("index.json").exists() {
+        let message = "The data folder already has an index. Move it away before migrating.";
+        return Err(AppError::new(ErrorCode::InvalidFolder, message).with_path(to));
+    }
+
+    fs::create_dir_all(to).map_err(|e| AppError::io(to, format!("create app dir error: {e}")))?;
+    for name in DATA_ENTRIES {
+        let source = from.join(name);
+        let result = if source.is_dir() {
+            copy_dir(&source, &to.join(name))
+        } else if source.is_file() && !kept_in_target(to, name) {
+            fs::copy(&source, to.join(name)).map(|_| ())
+        } else {
+            continue;
+        };
+        result.map_err(|e| AppError::io(&source, format!("Failed to copy {name}: {e}")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("data-dir-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn args(values: &[&str]) -> Vec<OsString> {
+        values.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn the_flag_wins_over_the_environment_over_portable_mode() {
+        let exe_dir = temp_folder();
+        fs::write(exe_dir.join(PORTABLE_FLAG_FILE), "").unwrap();
+        let default = PathBuf::from("/home/me/.config/cycle-planner");
+        let env = Some(OsString::from("/env"));
+        let resolve = |args: &[OsString], env: Option<OsString>, exe_dir: &Path| {
+            let dir = resolve_from(args, env, Some(exe_dir), default.clone());
+            (dir.path, dir.source)
+        };
+
+        let flag = args(&["--data-dir", "/flag"]);
+        assert_eq!(resolve(&flag, env.clone(), &exe_dir), (PathBuf::from("/flag"), DataDirSource::CommandLine));
+        let flag = args(&["--verbose", "--data-dir=/flag"]);
+        assert_eq!(resolve(&flag, env.clone(), &exe_dir), (PathBuf::from("/flag"), DataDirSource::CommandLine));
+        assert_eq!(resolve(&[], env, &exe_dir), (PathBuf::from("/env"), DataDirSource::Environment));
+        let portable = (exe_dir.join(PORTABLE_DATA_DIR), DataDirSource::Portable);
+        assert_eq!(resolve(&[], Some(OsString::new()), &exe_dir), portable);
+
+        fs::remove_file(exe_dir.join(PORTABLE_FLAG_FILE)).unwrap();
+        assert_eq!(resolve(&[], None, &exe_dir), (default.clone(), DataDirSource::Default));
+        assert_eq!(resolve_from(&args(&["--data-dir"]), None, None, default.clone()).path, default);
+        let _ = fs::remove_dir_all(&exe_dir);
+    }
+
+    #[test]
+    fn migrate_copies_the_index_settings_and_merge_bases() {
+        let from = temp_folder();
+        let to = temp_folder().join("new");
+        fs::create_dir_all(from.join("sync_bases")).unwrap();
+        fs::write(from.join("sync_bases").join("cycle_a.json"), "{}").unwrap();
+        for name in ["index.json", "index.json.bak", "settings.json", "window_state.json"] {
+            fs::write(from.join(name), name).unwrap();
+        }
+
+        migrate(&from, &to).unwrap();
+        for name in ["index.json", "index.json.bak", "settings.json", "window_state.json", "sync_bases/cycle_a.json"] {
+            assert!(to.join(name).is_file(), "{name} was not copied");
+        }
+        assert!(from.join("index.json").is_file());
+        let error = migrate(&from, &to).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidFolder);
+        assert_eq!(migrate(&to, &to).unwrap_err().code, ErrorCode::InvalidInput);
+        assert_eq!(migrate(&to.join("nothing"), &from).unwrap_err().code, ErrorCode::NotFound);
+        let _ = fs::remove_dir_all(&from);
+        let _ = fs::remove_dir_all(to.parent().unwrap());
+    }
+
+    #[test]
+    fn migrate_keeps_what_the_target_already_has() {
+        let from = temp_folder();
+        let to = temp_folder();
+        fs::create_dir_all(from.join("sync_bases")).unwrap();
+        fs::create_dir_all(to.join("sync_bases")).unwrap();
+        for name in ["index.json", "settings.json", "settings.json.bak", "window_state.json", "sync_bases/a.json"] {
+            fs::write(from.join(name), "old").unwrap();
+        }
+        for name in ["settings.json", "window_state.json.bak", "sync_bases/a.json"] {
+            fs::write(to.join(name), "new").unwrap();
+        }
+
+        migrate(&from, &to).unwrap();
+        assert_eq!(fs::read_to_string(to.join("index.json")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(to.join("settings.json")).unwrap(), "new");
+        assert!(!to.join("settings.json.bak").exists());
+        assert_eq!(fs::read_to_string(to.join("window_state.json")).unwrap(), "old");
+        assert_eq!(fs::read_to_string(to.join("window_state.json.bak")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(to.join("sync_bases/a.json")).unwrap(), "new");
+        let _ = fs::remove_dir_all(&from);
+        let _ = fs::remove_dir_all(&to);
+    }
+}
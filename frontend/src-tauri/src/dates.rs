@@ -0,0 +1,106 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+
+/// Format of start, end and due dates, which are plain days; timestamps are RFC 3339 with offset.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Unix times at or above this are taken as milliseconds rather than seconds (it is the year 5138
+/// in seconds and March 1973 in milliseconds).
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// The current time in the user's timezone, e.g. `2026-10-17T09:30:00.125+09:00`.
+pub fn now_iso() -> String {
+    Local::now().to_rfc3339_opts(SecondsFormat::Millis, false)
+}
+
+/// Reads an RFC 3339 timestamp, or a Unix time in seconds or milliseconds as written by older
+/// versions.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<FixedOffset>> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Some(parsed);
+    }
+    let number = value.parse::<i64>().ok()?;
+    let parsed = if number.abs() >= MILLIS_THRESHOLD {
+        Utc.timestamp_millis_opt(number)
+    } else {
+        Utc.timestamp_opt(number, 0)
+    };
+    parsed.single().map(|utc| utc.fixed_offset())
+}
+
+/// Rewrites a legacy Unix time as RFC 3339 (in UTC, the zone the number was counted in), keeping
+/// the milliseconds of a millisecond time. RFC 3339 values and anything unreadable are returned
+/// unchanged.
+pub fn normalize_timestamp(value: &str) -> String {
+    if DateTime::parse_from_rfc3339(value.trim()).is_ok() {
+        return value.to_string();
+    }
+    match parse_timestamp(value) {
+        Some(parsed) => parsed.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        None => value.to_string(),
+    }
+}
+
+/// The day `time` falls on in the user's timezone (the system's local time), which is what "today"
+/// and "overdue" mean to the user, rather than the UTC day.
+pub fn local_day<Tz: TimeZone>(time: &DateTime<Tz>) -> NaiveDate {
+    time.with_timezone(&Local).date_naive()
+}
+
+/// Reads a `YYYY-MM-DD` day, or the local day of a timestamp.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT)
+        .ok()
+        .or_else(|| parse_timestamp(value).map(|time| local_day(&time)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(value).unwrap()
+    }
+
+    #[test]
+    fn parses_rfc3339_with_its_offset() {
+        let parsed = parse_timestamp(" 2026-10-17T09:30:00.125+09:00 ").unwrap();
+        assert_eq!(parsed.offset().local_minus_utc(), 9 * 3600);
+        assert_eq!(parsed, utc("2026-10-17T00:30:00.125Z"));
+    }
+
+    #[test]
+    fn reads_unix_seconds_and_milliseconds_as_the_same_instant() {
+        assert_eq!(parse_timestamp("1700000000"), Some(utc("2023-11-14T22:13:20Z")));
+        assert_eq!(parse_timestamp("1700000000000"), Some(utc("2023-11-14T22:13:20Z")));
+        assert_eq!(parse_timestamp("1700000000123"), Some(utc("2023-11-14T22:13:20.123Z")));
+    }
+
+    #[test]
+    fn switches_to_milliseconds_at_the_threshold() {
+        assert_eq!(parse_timestamp("99999999999"), Some(utc("5138-11-16T09:46:39Z")));
+        assert_eq!(parse_timestamp("100000000000"), Some(utc("1973-03-03T09:46:40Z")));
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_timestamps() {
+        assert_eq!(parse_timestamp("yesterday"), None);
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("12.5"), None);
+    }
+
+    #[test]
+    fn normalizes_legacy_unix_times_and_leaves_the_rest() {
+        assert_eq!(normalize_timestamp("1700000000"), "2023-11-14T22:13:20Z");
+        assert_eq!(normalize_timestamp("1700000000123"), "2023-11-14T22:13:20.123Z");
+        assert_eq!(normalize_timestamp("2026-10-17T09:30:00+09:00"), "2026-10-17T09:30:00+09:00");
+        assert_eq!(normalize_timestamp("not a date"), "not a date");
+    }
+
+    #[test]
+    fn parses_plain_days_and_the_local_day_of_timestamps() {
+        assert_eq!(parse_date("2024-02-03"), NaiveDate::from_ymd_opt(2024, 2, 3));
+        assert_eq!(parse_date("1700000000"), Some(local_day(&utc("2023-11-14T22:13:20Z"))));
+        assert_eq!(parse_date("2024-02-30"), None);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::models::{CycleData, EntityKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeType {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// One goal, work or task that differs between two versions of a cycle. `before`/`after` hold
+/// the full entity as stored in `cycle_data.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityChange {
+    pub kind: EntityKind,
+    pub id: String,
+    pub change: ChangeType,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    /// Position in the list the entity was deleted from (or created in), so undo/redo can put it
+    /// back where it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+fn entries<T: Serialize>(items: &[T]) -> Vec<(String, Value)> {
+    items
+        .iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .map(|value| {
+            let id = value.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+            (id, value)
+        })
+        .collect()
+}
+
+fn diff_list<T: Serialize>(kind: EntityKind, from: &[T], to: &[T], changes: &mut Vec<EntityChange>) {
+    let (from, to) = (entries(from), entries(to));
+    for (position, (id, before)) in from.iter().enumerate() {
+        match to.iter().find(|(other, _)| other == id) {
+            None => changes.push(EntityChange {
+                kind,
+                id: id.clone(),
+                change: ChangeType::Deleted,
+                before: Some(before.clone()),
+                after: None,
+                position: Some(position),
+            }),
+            Some((_, after)) if after != before => changes.push(EntityChange {
+                kind,
+                id: id.clone(),
+                change: ChangeType::Updated,
+                before: Some(before.clone()),
+                after: Some(after.clone()),
+                position: None,
+            }),
+            Some(_) => {}
+        }
+    }
+    for (position, (id, after)) in to.iter().enumerate() {
+        if !from.iter().any(|(other, _)| other == id) {
+            changes.push(EntityChange {
+                kind,
+                id: id.clone(),
+                change: ChangeType::Created,
+                before: None,
+                after: Some(after.clone()),
+                position: Some(position),
+            });
+        }
+    }
+}
+
+/// Entity-level changes that turn `from` into `to`.
+pub fn diff_cycle_data(from: &CycleData, to: &CycleData) -> Vec<EntityChange> {
+    let mut changes = vec![];
+    diff_list(EntityKind::Goal, &from.goals, &to.goals, &mut changes);
+    diff_list(EntityKind::Work, &from.works, &to.works, &mut changes);
+    diff_list(EntityKind::Task, &from.tasks, &to.tasks, &mut changes);
+    changes
+}
@@ -0,0 +1,205 @@
+use crate::dates::DATE_FORMAT;
+use crate::models::{CycleData, CycleMeta, Goal, Task, Work, WorkStatus};
+use crate::uid;
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeDelta};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateOptions {
+    /// Puts every Work back to `NOT_STARTED` and unchecks every Task.
+    #[serde(default)]
+    pub reset_statuses: bool,
+    /// Days added to every start, end and due date (negative moves them back).
+    #[serde(default)]
+    pub shift_days: i64,
+}
+
+/// Shifts a `YYYY-MM-DD` (or RFC 3339) date by `days`; anything unparsable is kept as is. Timestamps
+/// keep their offset, `Z` and fractional seconds, so a shift changes nothing but the day.
+pub fn shift_date(value: &str, days: i64) -> String {
+    let Some(delta) = TimeDelta::try_days(days).filter(|_| days != 0) else {
+        return value.to_string();
+    };
+    if let Ok(date) = NaiveDate::parse_from_str(value, DATE_FORMAT) {
+        if let Some(shifted) = date.checked_add_signed(delta) {
+            return shifted.format(DATE_FORMAT).to_string();
+        }
+    } else if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        if let Some(shifted) = date_time.checked_add_signed(delta) {
+            return shifted.to_rfc3339_opts(SecondsFormat::AutoSi, value.ends_with(['Z', 'z']));
+        }
+    }
+    value.to_string()
+}
+
+fn shift_optional(value: &Option<String>, days: i64) -> Option<String> {
+    value.as_deref().map(|v| shift_date(v, days))
+}
+
+/// Copies entities into another cycle under fresh ids, remembering the old -> new id mapping so
+/// Works follow their Goal and Tasks follow their Work.
+pub struct EntityCopier<'a> {
+    cycle_id: &'a str,
+    options: &'a DuplicateOptions,
+    goal_ids: HashMap<String, String>,
+    work_ids: HashMap<String, String>,
+}
+
+impl<'a> EntityCopier<'a> {
+    pub fn new(cycle_id: &'a str, options: &'a DuplicateOptions) -> Self {
+        Self {
+            cycle_id,
+            options,
+            goal_ids: HashMap::new(),
+            work_ids: HashMap::new(),
+        }
+    }
+
+    pub fn copy_goal(&mut self, goal: &Goal) -> Goal {
+        let id = uid("goal");
+        self.goal_ids.insert(goal.id.clone(), id.clone());
+        Goal {
+            id,
+            cycle_id: self.cycle_id.to_string(),
+            start_date: shift_optional(&goal.start_date, self.options.shift_days),
+            end_date: shift_optional(&goal.end_date, self.options.shift_days),
+            ..goal.clone()
+        }
+    }
+
+    /// Must run after the Work's Goal was copied; a Goal that was not copied is dropped.
+    pub fn copy_work(&mut self, work: &Work) -> Work {
+        let id = uid("work");
+        self.work_ids.insert(work.id.clone(), id.clone());
+        Work {
+            id,
+            cycle_id: self.cycle_id.to_string(),
+            goal_id: work.goal_id.as_ref().and_then(|g| self.goal_ids.get(g).cloned()),
+            status: if self.options.reset_statuses {
+                WorkStatus::NotStarted
+            } else {
+                work.status.clone()
+            },
+            start_date: shift_optional(&work.start_date, self.options.shift_days),
+            end_date: shift_optional(&work.end_date, self.options.shift_days),
+            ..work.clone()
+        }
+    }
+
+    /// Returns `None` when the Task's Work was not copied.
+    pub fn copy_task(&mut self, task: &Task) -> Option<Task> {
+        let work_id = self.work_ids.get(&task.work_id)?.clone();
+        Some(Task {
+            id: uid("task"),
+            cycle_id: self.cycle_id.to_string(),
+            work_id,
+            done: task.done && !self.options.reset_statuses,
+            due_date: shift_optional(&task.due_date, self.options.shift_days),
+            ..task.clone()
+        })
+    }
+}
+
+pub fn duplicate_cycle_data(source: &CycleData, target: &CycleMeta, options: &DuplicateOptions) -> CycleData {
+    let mut data = CycleData::empty(target);
+    let mut copier = EntityCopier::new(&target.id, options);
+    data.goals = source.goals.iter().map(|g| copier.copy_goal(g)).collect();
+    data.works = source.works.iter().map(|w| copier.copy_work(w)).collect();
+    data.tasks = source.tasks.iter().filter_map(|t| copier.copy_task(t)).collect();
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options(reset_statuses: bool, shift_days: i64) -> DuplicateOptions {
+        DuplicateOptions { reset_statuses, shift_days }
+    }
+
+    fn source() -> CycleData {
+        serde_json::from_value(json!({
+            "schemaVersion": 1,
+            "id": "cycle_a",
+            "name": "A",
+            "createdAt": "2026-10-01T09:00:00.000+09:00",
+            "goals": [{ "id": "g1", "cycleId": "cycle_a", "title": "Ship", "startDate": "2026-10-01" }],
+            "works": [
+                { "id": "w1", "cycleId": "cycle_a", "goalId": "g1", "title": "Plan", "status": "DONE" },
+                { "id": "w2", "cycleId": "cycle_a", "goalId": "g_gone", "title": "Build", "status": "IN_PROGRESS" }
+            ],
+            "tasks": [
+                { "id": "t1", "cycleId": "cycle_a", "workId": "w1", "title": "Draft", "done": true,
+                  "dueDate": "2026-10-03" },
+                { "id": "t2", "cycleId": "cycle_a", "workId": "w2", "title": "Code", "done": false },
+                { "id": "t3", "cycleId": "cycle_a", "workId": "w_gone", "title": "Orphan", "done": false }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn target() -> CycleMeta {
+        serde_json::from_value(json!({
+            "id": "cycle_b",
+            "name": "B",
+            "createdAt": "2026-11-01T09:00:00.000+09:00",
+            "folderPath": "/cycles/B"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn shifts_plain_days() {
+        assert_eq!(shift_date("2026-10-30", 3), "2026-11-02");
+        assert_eq!(shift_date("2024-03-01", -1), "2024-02-29");
+        assert_eq!(shift_date("2026-10-30", 0), "2026-10-30");
+    }
+
+    #[test]
+    fn shifts_timestamps_keeping_their_format() {
+        assert_eq!(shift_date("2026-10-17T09:30:00.125+09:00", 1), "2026-10-18T09:30:00.125+09:00");
+        assert_eq!(shift_date("2026-10-17T09:30:00+09:00", -7), "2026-10-10T09:30:00+09:00");
+        assert_eq!(shift_date("2026-10-17T00:30:00Z", 1), "2026-10-18T00:30:00Z");
+    }
+
+    #[test]
+    fn keeps_values_it_cannot_shift() {
+        assert_eq!(shift_date("next week", 3), "next week");
+        assert_eq!(shift_date("2026-10-17", i64::MAX), "2026-10-17");
+        assert_eq!(shift_date("+262142-12-31", 1), "+262142-12-31");
+        assert_eq!(shift_date("2026-10-17T09:30:00Z", 200_000_000), "2026-10-17T09:30:00Z");
+    }
+
+    #[test]
+    fn copies_follow_the_new_ids_of_their_parents() {
+        let data = duplicate_cycle_data(&source(), &target(), &options(false, 0));
+
+        assert_eq!((data.id.as_str(), data.name.as_str()), ("cycle_b", "B"));
+        let goal = &data.goals[0];
+        assert!(goal.id.starts_with("goal_") && goal.cycle_id == "cycle_b");
+        assert_eq!(data.works[0].goal_id.as_deref(), Some(goal.id.as_str()));
+        assert_eq!(data.works[1].goal_id, None);
+        assert!(data.works.iter().all(|w| w.id.starts_with("work_") && w.cycle_id == "cycle_b"));
+        assert_eq!(data.works[0].status, WorkStatus::Done);
+
+        assert_eq!(data.tasks.len(), 2);
+        assert_eq!(data.tasks[0].work_id, data.works[0].id);
+        assert_eq!(data.tasks[1].work_id, data.works[1].id);
+        assert!(data.tasks[0].done);
+        assert!(data.tasks.iter().all(|t| t.id.starts_with("task_") && t.cycle_id == "cycle_b"));
+    }
+
+    #[test]
+    fn resets_statuses_and_shifts_dates_on_request() {
+        let data = duplicate_cycle_data(&source(), &target(), &options(true, 7));
+
+        assert_eq!(data.goals[0].start_date.as_deref(), Some("2026-10-08"));
+        assert!(data.works.iter().all(|w| w.status == WorkStatus::NotStarted));
+        assert!(data.tasks.iter().all(|t| !t.done));
+        assert_eq!(data.tasks[0].due_date.as_deref(), Some("2026-10-10"));
+        assert_eq!(data.tasks[1].due_date, None);
+    }
+}
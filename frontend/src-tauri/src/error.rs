@@ -0,0 +1,127 @@
+use crate::i18n::{self, Text};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+/// What went wrong, as far as the UI cares: each code maps to one way of recovering. The
+/// serialized names are part of the command API and must not change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// No cycle with this id is registered; reload the index.
+    CycleNotFound,
+    /// The cycle is registered but its folder is gone; relocate it.
+    FolderMissing,
+    /// A folder picked by the user cannot be used.
+    InvalidFolder,
+    /// The folder holds no cycle data to import or relocate to.
+    CycleDataNotFound,
+    /// A file exists but cannot be parsed; restore it from a backup or snapshot.
+    CorruptData,
+    /// A file was written by a newer version of the app; update the app.
+    NewerSchema,
+    /// Reading or writing failed (permissions, disk full, file locked); retry.
+    Io,
+    /// The cycle data changed on disk after it was loaded; reload before saving.
+    ExternalChange,
+    /// A goal, work, task, snapshot or conflict copy named in the request does not exist.
+    NotFound,
+    /// The request itself is not acceptable (empty name, unknown status, ...).
+    InvalidInput,
+    /// A window or tray operation failed.
+    Window,
+    Internal,
+}
+
+/// Error returned by every command, serialized as `{ code, message, detail, path?, cycleId? }`:
+/// `message` is the text for `code` in the current locale and `detail` the specific message
+/// recorded where the error happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub path: Option<String>,
+    pub cycle_id: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            path: None,
+            cycle_id: None,
+        }
+    }
+
+    pub fn with_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.path = Some(path.as_ref().to_string_lossy().to_string());
+        self
+    }
+
+    /// Sets the path unless a more specific one was recorded where the error happened.
+    pub fn or_path(self, path: impl AsRef<Path>) -> Self {
+        if self.path.is_some() {
+            return self;
+        }
+        self.with_path(path)
+    }
+
+    pub fn with_cycle(mut self, cycle_id: &str) -> Self {
+        self.cycle_id = Some(cycle_id.to_string());
+        self
+    }
+
+    pub fn cycle_not_found(cycle_id: &str) -> Self {
+        Self::new(ErrorCode::CycleNotFound, "Cycle was not found.").with_cycle(cycle_id)
+    }
+
+    pub fn io(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Io, message).with_path(path)
+    }
+
+    pub fn corrupt(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::CorruptData, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn window(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Window, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 5)?;
+        state.serialize_field("code", &self.code)?;
+        state.serialize_field("message", i18n::text(i18n::current(), Text::Error(self.code)))?;
+        state.serialize_field("detail", &self.message)?;
+        if let Some(path) = &self.path {
+            state.serialize_field("path", path)?;
+        }
+        if let Some(cycle_id) = &self.cycle_id {
+            state.serialize_field("cycleId", cycle_id)?;
+        }
+        state.end()
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,197 @@
+use crate::atomic_file::write_json_atomic;
+use crate::dates::local_day;
+use crate::error::AppError;
+use crate::models::{CycleData, StorageKind};
+use crate::read_cycle_file;
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const HISTORY_DIR: &str = ".history";
+const SNAPSHOT_ID_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    /// File stem inside `.history/`, e.g. `20261017T101530.123Z`.
+    pub id: String,
+    pub created_at: String,
+    pub size: u64,
+}
+
+pub fn history_dir(folder: &str) -> PathBuf {
+    Path::new(folder).join(HISTORY_DIR)
+}
+
+fn parse_snapshot_id(id: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(id, SNAPSHOT_ID_FORMAT)
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn snapshot_files(folder: &str) -> Vec<(DateTime<Utc>, String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(history_dir(folder)) else {
+        return vec![];
+    };
+    let mut snapshots = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            Some((parse_snapshot_id(&id)?, id, path))
+        })
+        .collect::<Vec<_>>();
+    snapshots.sort_by_key(|(taken_at, _, _)| std::cmp::Reverse(*taken_at));
+    snapshots
+}
+
+/// Snapshots to delete so that every snapshot of the last hour is kept, the newest one per hour
+/// for the last day and the newest one per local day for the last 30 days. `snapshots` must be sorted
+/// newest first.
+fn expired_snapshots(snapshots: &[(DateTime<Utc>, String, PathBuf)], now: DateTime<Utc>) -> Vec<PathBuf> {
+    let mut kept_hours = HashSet::new();
+    let mut kept_days = HashSet::new();
+    let mut expired = vec![];
+    for (taken_at, _, path) in snapshots {
+        let age = now - *taken_at;
+        let keep = if age <= TimeDelta::hours(1) {
+            true
+        } else if age <= TimeDelta::days(1) {
+            kept_hours.insert(taken_at.format("%Y%m%d%H").to_string())
+        } else if age <= TimeDelta::days(30) {
+            kept_days.insert(local_day(taken_at))
+        } else {
+            false
+        };
+        if !keep {
+            expired.push(path.clone());
+        }
+    }
+    expired
+}
+
+/// Shortest time between two snapshots. A JSON cycle rewrites its whole file on every save, so a
+/// snapshot per save costs about as much as the save itself; a SQLite cycle only writes the rows
+/// that changed, and a full snapshot on every save would undo that.
+fn snapshot_interval(kind: StorageKind) -> TimeDelta {
+    match kind {
+        StorageKind::Json => TimeDelta::zero(),
+        StorageKind::Sqlite => TimeDelta::minutes(10),
+    }
+}
+
+fn snapshot_due(newest: Option<DateTime<Utc>>, now: DateTime<Utc>, kind: StorageKind) -> bool {
+    newest.is_none_or(|taken_at| now - taken_at >= snapshot_interval(kind))
+}
+
+/// Stores `data` as a new snapshot, unless the newest one is more recent than the interval of
+/// `kind`, and applies the retention policy.
+pub fn record_snapshot(folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError> {
+    let now = Utc::now();
+    let newest = snapshot_files(folder).first().map(|(taken_at, _, _)| *taken_at);
+    if !snapshot_due(newest, now, kind) {
+        return Ok(());
+    }
+    let dir = history_dir(folder);
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, format!("create history dir error: {e}")))?;
+    let id = now.format(SNAPSHOT_ID_FORMAT).to_string();
+    write_json_atomic(&dir.join(format!("{id}.json")), data, "snapshot")?;
+
+    for path in expired_snapshots(&snapshot_files(folder), now) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Newest first.
+pub fn list_snapshots(folder: &str) -> Vec<SnapshotInfo> {
+    snapshot_files(folder)
+        .into_iter()
+        .map(|(taken_at, id, path)| SnapshotInfo {
+            id,
+            created_at: taken_at.to_rfc3339(),
+            size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        })
+        .collect()
+}
+
+pub fn read_snapshot(folder: &str, snapshot_id: &str) -> Result<CycleData, AppError> {
+    if parse_snapshot_id(snapshot_id).is_none() {
+        return Err(AppError::not_found("Snapshot was not found."));
+    }
+    let path = history_dir(folder).join(format!("{snapshot_id}.json"));
+    read_cycle_file(&path)?.ok_or_else(|| AppError::not_found("Snapshot was not found."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().with_timezone(&Utc)
+    }
+
+    /// Snapshots taken at `times`, newest first like `snapshot_files` returns them.
+    fn snapshots(times: &[&str]) -> Vec<(DateTime<Utc>, String, PathBuf)> {
+        let mut snapshots = times
+            .iter()
+            .map(|time| {
+                let id = at(time).format(SNAPSHOT_ID_FORMAT).to_string();
+                (at(time), id.clone(), PathBuf::from(format!("{id}.json")))
+            })
+            .collect::<Vec<_>>();
+        snapshots.sort_by_key(|(taken_at, _, _)| std::cmp::Reverse(*taken_at));
+        snapshots
+    }
+
+    fn expired(times: &[&str]) -> Vec<String> {
+        let now = at("2026-10-17T12:00:00Z");
+        let mut expired = expired_snapshots(&snapshots(times), now)
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        expired.sort();
+        expired
+    }
+
+    fn file(time: &str) -> String {
+        format!("{}.json", at(time).format(SNAPSHOT_ID_FORMAT))
+    }
+
+    #[test]
+    fn keeps_every_snapshot_of_the_last_hour() {
+        assert!(expired(&["2026-10-17T11:59:59Z", "2026-10-17T11:30:00Z", "2026-10-17T11:00:00Z"]).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_per_hour_for_a_day() {
+        let times = ["2026-10-17T09:50:00Z", "2026-10-17T09:10:00Z", "2026-10-17T08:30:00Z", "2026-10-16T13:00:00Z"];
+        assert_eq!(expired(&times), [file("2026-10-17T09:10:00Z")]);
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_per_day_for_thirty_days() {
+        let times = ["2026-10-14T12:14:00Z", "2026-10-14T12:10:00Z", "2026-10-10T12:10:00Z"];
+        assert_eq!(expired(&times), [file("2026-10-14T12:10:00Z")]);
+    }
+
+    #[test]
+    fn sqlite_cycles_snapshot_at_most_every_interval() {
+        let now = at("2026-10-17T12:00:00Z");
+        assert!(snapshot_due(None, now, StorageKind::Sqlite));
+        assert!(!snapshot_due(Some(at("2026-10-17T11:55:00Z")), now, StorageKind::Sqlite));
+        assert!(snapshot_due(Some(at("2026-10-17T11:50:00Z")), now, StorageKind::Sqlite));
+        assert!(snapshot_due(Some(now), now, StorageKind::Json));
+    }
+
+    #[test]
+    fn drops_snapshots_older_than_thirty_days() {
+        let times = ["2026-09-18T12:00:00Z", "2026-09-01T12:00:00Z"];
+        assert_eq!(expired(&times), [file("2026-09-01T12:00:00Z")]);
+    }
+}
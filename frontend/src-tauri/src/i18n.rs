@@ -0,0 +1,102 @@
+use crate::error::ErrorCode;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Ko,
+}
+
+impl Locale {
+    /// Reads a BCP 47 or POSIX tag (`ko-KR`, `ko_KR.UTF-8`); anything that is not Korean is English.
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.trim().to_ascii_lowercase().starts_with("ko") {
+            Locale::Ko
+        } else {
+            Locale::En
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+pub fn system_locale() -> Locale {
+    use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+    let mut buffer = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    if len <= 1 {
+        return Locale::En;
+    }
+    Locale::from_tag(&String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn system_locale() -> Locale {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| Locale::from_tag(&value))
+        .unwrap_or_default()
+}
+
+/// Language of every message the backend produces from now on.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn current() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::Ko,
+        _ => Locale::En,
+    }
+}
+
+pub fn set_current(locale: Locale) {
+    CURRENT.store(locale as u8, Ordering::Relaxed);
+}
+
+/// Every user-facing string the backend shows. Matching on `ErrorCode` without a wildcard keeps
+/// new codes from shipping untranslated.
+#[derive(Debug, Clone, Copy)]
+pub enum Text {
+    TrayQuit,
+    Error(ErrorCode),
+}
+
+pub fn text(locale: Locale, text: Text) -> &'static str {
+    use ErrorCode::*;
+    match (text, locale) {
+        (Text::TrayQuit, Locale::En) => "Quit",
+        (Text::TrayQuit, Locale::Ko) => "종료",
+        (Text::Error(code), Locale::En) => match code {
+            CycleNotFound => "The Cycle was not found. It may have been removed from the list.",
+            FolderMissing => "The Cycle folder was not found. Relocate the Cycle to its new folder.",
+            InvalidFolder => "The selected folder cannot be used.",
+            CycleDataNotFound => "No Cycle data was found in the folder.",
+            CorruptData => "The saved data could not be read. Restore it from a backup or snapshot.",
+            NewerSchema => "The data was saved by a newer version of Cycle. Update the app to open it.",
+            Io => "Reading or writing a file failed. Check the folder and try again.",
+            ExternalChange => "The Cycle was changed outside the app. Reload it before saving.",
+            NotFound => "The requested item does not exist.",
+            InvalidInput => "The request is not valid.",
+            Window => "The window operation failed.",
+            Internal => "An unexpected error occurred.",
+        },
+        (Text::Error(code), Locale::Ko) => match code {
+            CycleNotFound => "Cycle을 찾을 수 없습니다. 목록에서 제거되었을 수 있습니다.",
+            FolderMissing => "Cycle 폴더를 찾을 수 없습니다. 옮겨진 폴더로 Cycle 위치를 다시 지정하세요.",
+            InvalidFolder => "선택한 폴더를 사용할 수 없습니다.",
+            CycleDataNotFound => "폴더에서 Cycle 데이터를 찾을 수 없습니다.",
+            CorruptData => "저장된 데이터를 읽을 수 없습니다. 백업이나 스냅샷에서 복원하세요.",
+            NewerSchema => "더 새로운 버전의 Cycle에서 저장된 데이터입니다. 앱을 업데이트한 뒤 여세요.",
+            Io => "파일을 읽거나 쓰지 못했습니다. 폴더를 확인한 뒤 다시 시도하세요.",
+            ExternalChange => "앱 밖에서 Cycle이 변경되었습니다. 저장하기 전에 다시 불러오세요.",
+            NotFound => "요청한 항목이 없습니다.",
+            InvalidInput => "요청이 올바르지 않습니다.",
+            Window => "창 작업에 실패했습니다.",
+            Internal => "예기치 않은 오류가 발생했습니다.",
+        },
+    }
+}
@@ -0,0 +1,359 @@
+use crate::dates::parse_date;
+use crate::models::{CycleData, EntityKind, Goal, Work, WorkStatus};
+use crate::uid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub const UNSORTED_TITLE: &str = "Unsorted";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Problem {
+    /// A Work points at a missing Goal, or a Task at a missing Work.
+    Orphan,
+    DuplicateId,
+    InvalidStatus,
+    StartAfterEnd,
+    CycleIdMismatch,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityIssue {
+    pub kind: EntityKind,
+    pub id: String,
+    pub problem: Problem,
+    pub message: String,
+}
+
+/// Which fixes `repair_cycle` should apply; everything is opt-in.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepairOptions {
+    /// Move orphaned Works under an "Unsorted" Goal and orphaned Tasks under an "Unsorted" Work.
+    pub reattach_orphans: bool,
+    /// Give every repeated id after the first one a fresh id.
+    pub dedupe_ids: bool,
+    /// Set every entity's `cycleId` to the id of the cycle it is stored in.
+    pub rewrite_cycle_ids: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub data: CycleData,
+    pub fixed: usize,
+    /// Issues the selected fixes don't cover.
+    pub remaining: Vec<IntegrityIssue>,
+}
+
+fn issue(kind: EntityKind, id: &str, problem: Problem, message: String) -> IntegrityIssue {
+    IntegrityIssue {
+        kind,
+        id: id.to_string(),
+        problem,
+        message,
+    }
+}
+
+fn check_ids<'a>(kind: EntityKind, ids: impl Iterator<Item = &'a str>, issues: &mut Vec<IntegrityIssue>) {
+    let mut seen = HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            issues.push(issue(kind, id, Problem::DuplicateId, format!("Id {id} is used more than once.")));
+        }
+    }
+}
+
+fn check_cycle_id(kind: EntityKind, id: &str, cycle_id: &str, expected: &str, issues: &mut Vec<IntegrityIssue>) {
+    if cycle_id != expected {
+        issues.push(issue(
+            kind,
+            id,
+            Problem::CycleIdMismatch,
+            format!("cycleId is \"{cycle_id}\" but the entity is stored in Cycle {expected}."),
+        ));
+    }
+}
+
+fn check_dates(kind: EntityKind, id: &str, start: &Option<String>, end: &Option<String>, issues: &mut Vec<IntegrityIssue>) {
+    let parse = |date: &Option<String>| date.as_deref().and_then(parse_date);
+    if let (Some(start), Some(end)) = (parse(start), parse(end)) {
+        if start > end {
+            issues.push(issue(
+                kind,
+                id,
+                Problem::StartAfterEnd,
+                format!("Start date {start} is after end date {end}."),
+            ));
+        }
+    }
+}
+
+/// Reports every integrity problem in `data`. An empty list means the cycle is consistent.
+pub fn validate_cycle_data(data: &CycleData) -> Vec<IntegrityIssue> {
+    let mut issues = vec![];
+    check_ids(EntityKind::Goal, data.goals.iter().map(|g| g.id.as_str()), &mut issues);
+    check_ids(EntityKind::Work, data.works.iter().map(|w| w.id.as_str()), &mut issues);
+    check_ids(EntityKind::Task, data.tasks.iter().map(|t| t.id.as_str()), &mut issues);
+
+    let goal_ids = data.goals.iter().map(|g| g.id.as_str()).collect::<HashSet<_>>();
+    let work_ids = data.works.iter().map(|w| w.id.as_str()).collect::<HashSet<_>>();
+
+    for goal in &data.goals {
+        check_cycle_id(EntityKind::Goal, &goal.id, &goal.cycle_id, &data.id, &mut issues);
+        check_dates(EntityKind::Goal, &goal.id, &goal.start_date, &goal.end_date, &mut issues);
+    }
+    for work in &data.works {
+        check_cycle_id(EntityKind::Work, &work.id, &work.cycle_id, &data.id, &mut issues);
+        check_dates(EntityKind::Work, &work.id, &work.start_date, &work.end_date, &mut issues);
+        if let Some(goal_id) = work.goal_id.as_deref().filter(|id| !goal_ids.contains(id)) {
+            issues.push(issue(
+                EntityKind::Work,
+                &work.id,
+                Problem::Orphan,
+                format!("Goal {goal_id} does not exist in this Cycle."),
+            ));
+        }
+        if !work.status.is_known() {
+            issues.push(issue(
+                EntityKind::Work,
+                &work.id,
+                Problem::InvalidStatus,
+                format!("\"{}\" is not a valid Work status.", work.status.as_str()),
+            ));
+        }
+    }
+    for task in &data.tasks {
+        check_cycle_id(EntityKind::Task, &task.id, &task.cycle_id, &data.id, &mut issues);
+        if !work_ids.contains(task.work_id.as_str()) {
+            issues.push(issue(
+                EntityKind::Task,
+                &task.id,
+                Problem::Orphan,
+                format!("Work {} does not exist in this Cycle.", task.work_id),
+            ));
+        }
+    }
+    issues
+}
+
+fn dedupe<T>(items: &mut [T], id: impl Fn(&mut T) -> &mut String, prefix: &str) {
+    let mut seen = HashSet::new();
+    for item in items {
+        let id = id(item);
+        if !seen.insert(id.clone()) {
+            *id = uid(prefix);
+        }
+    }
+}
+
+fn unsorted_goal(data: &mut CycleData) -> String {
+    if let Some(goal) = data.goals.iter().find(|g| g.title == UNSORTED_TITLE) {
+        return goal.id.clone();
+    }
+    let goal = Goal {
+        id: uid("goal"),
+        cycle_id: data.id.clone(),
+        title: UNSORTED_TITLE.to_string(),
+        start_date: None,
+        end_date: None,
+        rolled_over_from: None,
+        extra: Default::default(),
+    };
+    let id = goal.id.clone();
+    data.goals.push(goal);
+    id
+}
+
+fn unsorted_work(data: &mut CycleData) -> String {
+    let goal_id = unsorted_goal(data);
+    if let Some(work) = data
+        .works
+        .iter()
+        .find(|w| w.title == UNSORTED_TITLE && w.goal_id.as_deref() == Some(goal_id.as_str()))
+    {
+        return work.id.clone();
+    }
+    let work = Work {
+        id: uid("work"),
+        cycle_id: data.id.clone(),
+        goal_id: Some(goal_id),
+        title: UNSORTED_TITLE.to_string(),
+        status: WorkStatus::NotStarted,
+        start_date: None,
+        end_date: None,
+        body: None,
+        rolled_over_from: None,
+        extra: Default::default(),
+    };
+    let id = work.id.clone();
+    data.works.push(work);
+    id
+}
+
+fn reattach_orphans(data: &mut CycleData) {
+    let goal_ids = data.goals.iter().map(|g| g.id.clone()).collect::<HashSet<_>>();
+    if data
+        .works
+        .iter()
+        .any(|w| w.goal_id.as_ref().is_some_and(|id| !goal_ids.contains(id)))
+    {
+        let unsorted = unsorted_goal(data);
+        for work in &mut data.works {
+            if work.goal_id.as_ref().is_some_and(|id| !goal_ids.contains(id)) {
+                work.goal_id = Some(unsorted.clone());
+            }
+        }
+    }
+
+    let work_ids = data.works.iter().map(|w| w.id.clone()).collect::<HashSet<_>>();
+    if data.tasks.iter().any(|t| !work_ids.contains(&t.work_id)) {
+        let unsorted = unsorted_work(data);
+        for task in &mut data.tasks {
+            if !work_ids.contains(&task.work_id) {
+                task.work_id = unsorted.clone();
+            }
+        }
+    }
+}
+
+/// Applies the selected fixes in place. Ids are deduplicated before orphans are reattached so a
+/// renamed duplicate never ends up as a parent.
+pub fn repair_cycle_data(data: &mut CycleData, options: RepairOptions) {
+    if options.rewrite_cycle_ids {
+        let cycle_id = data.id.clone();
+        data.goals.iter_mut().for_each(|g| g.cycle_id = cycle_id.clone());
+        data.works.iter_mut().for_each(|w| w.cycle_id = cycle_id.clone());
+        data.tasks.iter_mut().for_each(|t| t.cycle_id = cycle_id.clone());
+    }
+    if options.dedupe_ids {
+        dedupe(&mut data.goals, |g| &mut g.id, "goal");
+        dedupe(&mut data.works, |w| &mut w.id, "work");
+        dedupe(&mut data.tasks, |t| &mut t.id, "task");
+    }
+    if options.reattach_orphans {
+        reattach_orphans(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn cycle(goals: Value, works: Value, tasks: Value) -> CycleData {
+        serde_json::from_value(json!({
+            "schemaVersion": 2,
+            "id": "c1",
+            "name": "Cycle",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "goals": goals,
+            "works": works,
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn problems(data: &CycleData) -> Vec<(String, Problem)> {
+        validate_cycle_data(data).into_iter().map(|i| (i.id, i.problem)).collect()
+    }
+
+    fn all_fixes() -> RepairOptions {
+        RepairOptions {
+            reattach_orphans: true,
+            dedupe_ids: true,
+            rewrite_cycle_ids: true,
+        }
+    }
+
+    #[test]
+    fn reports_every_kind_of_problem() {
+        let data = cycle(
+            json!([{ "id": "g1", "cycleId": "c1", "startDate": "2026-03-01", "endDate": "2026-02-01" }]),
+            json!([
+                { "id": "w1", "cycleId": "c1", "goalId": "g1", "status": "NOT_STARTED" },
+                { "id": "w1", "cycleId": "c1", "goalId": "g1", "status": "NOT_STARTED" },
+                { "id": "w2", "cycleId": "c1", "goalId": "gone", "status": "PAUSED" },
+            ]),
+            json!([{ "id": "t1", "cycleId": "other", "workId": "missing" }]),
+        );
+
+        let found = problems(&data);
+
+        for expected in [
+            ("w1", Problem::DuplicateId),
+            ("g1", Problem::StartAfterEnd),
+            ("w2", Problem::Orphan),
+            ("w2", Problem::InvalidStatus),
+            ("t1", Problem::CycleIdMismatch),
+            ("t1", Problem::Orphan),
+        ] {
+            assert!(found.contains(&(expected.0.to_string(), expected.1)), "missing {expected:?} in {found:?}");
+        }
+        assert_eq!(found.len(), 6);
+    }
+
+    #[test]
+    fn a_consistent_cycle_has_no_issues() {
+        let data = cycle(
+            json!([{ "id": "g1", "cycleId": "c1" }]),
+            json!([{ "id": "w1", "cycleId": "c1", "goalId": "g1", "status": "DONE" }]),
+            json!([{ "id": "t1", "cycleId": "c1", "workId": "w1" }]),
+        );
+        assert!(validate_cycle_data(&data).is_empty());
+    }
+
+    #[test]
+    fn repair_fixes_what_it_covers_and_leaves_the_rest() {
+        let mut data = cycle(
+            json!([{ "id": "g1", "cycleId": "old" }]),
+            json!([
+                { "id": "w1", "cycleId": "c1", "goalId": "g1", "status": "NOT_STARTED" },
+                { "id": "w1", "cycleId": "c1", "goalId": "gone", "status": "PAUSED" },
+            ]),
+            json!([{ "id": "t1", "cycleId": "c1", "workId": "missing" }]),
+        );
+
+        repair_cycle_data(&mut data, all_fixes());
+
+        assert_eq!(data.works[0].id, "w1");
+        assert_ne!(data.works[1].id, "w1");
+        assert_eq!(data.goals[0].cycle_id, "c1");
+        let unsorted_goal = data.goals.iter().find(|g| g.title == UNSORTED_TITLE).unwrap();
+        assert_eq!(data.works[1].goal_id.as_deref(), Some(unsorted_goal.id.as_str()));
+        let unsorted_work = data.works.iter().find(|w| w.title == UNSORTED_TITLE).unwrap();
+        assert_eq!(unsorted_work.goal_id.as_deref(), Some(unsorted_goal.id.as_str()));
+        assert_eq!(data.tasks[0].work_id, unsorted_work.id);
+        assert_eq!(data.goals.len(), 2, "orphan Works and Tasks share one Unsorted Goal");
+        assert_eq!(problems(&data), [(data.works[1].id.clone(), Problem::InvalidStatus)]);
+    }
+
+    #[test]
+    fn repair_reuses_an_existing_unsorted_goal() {
+        let mut data = cycle(
+            json!([{ "id": "g-unsorted", "cycleId": "c1", "title": UNSORTED_TITLE }]),
+            json!([{ "id": "w1", "cycleId": "c1", "goalId": "gone", "status": "NOT_STARTED" }]),
+            json!([]),
+        );
+
+        repair_cycle_data(&mut data, all_fixes());
+
+        assert_eq!(data.goals.len(), 1);
+        assert_eq!(data.works[0].goal_id.as_deref(), Some("g-unsorted"));
+    }
+
+    #[test]
+    fn repair_without_options_changes_nothing() {
+        let mut data = cycle(
+            json!([]),
+            json!([{ "id": "w1", "cycleId": "old", "goalId": "gone", "status": "NOT_STARTED" }]),
+            json!([]),
+        );
+        let before = serde_json::to_value(&data).unwrap();
+
+        repair_cycle_data(&mut data, RepairOptions::default());
+
+        assert_eq!(serde_json::to_value(&data).unwrap(), before);
+    }
+}
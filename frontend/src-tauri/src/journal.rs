@@ -0,0 +1,298 @@
+use crate::atomic_file::{read_with_backup, write_json_atomic};
+use crate::dates::{normalize_timestamp, now_iso};
+use crate::diff::{diff_cycle_data, EntityChange};
+use crate::error::AppError;
+use crate::history::history_dir;
+use crate::models::{CycleData, EntityKind};
+use crate::uid;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Older entries are dropped once the undo stack grows past this.
+const MAX_JOURNAL_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub id: String,
+    pub at: String,
+    pub changes: Vec<EntityChange>,
+}
+
+/// Undo/redo stacks of entity-level changes, stored as `.history/journal.json` in the cycle folder
+/// so they outlive the app session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Journal {
+    #[serde(default)]
+    pub undo: Vec<JournalEntry>,
+    #[serde(default)]
+    pub redo: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalStatus {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub undo_count: usize,
+    pub redo_count: usize,
+    pub last_change_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Undo,
+    Redo,
+}
+
+fn journal_path(folder: &str) -> PathBuf {
+    history_dir(folder).join("journal.json")
+}
+
+pub fn load_journal(folder: &str) -> Result<Journal, AppError> {
+    let loaded = read_with_backup(&journal_path(folder), "journal", |raw| {
+        serde_json::from_str::<Journal>(raw).map_err(|e| AppError::corrupt(format!("parse journal error: {e}")))
+    })?;
+    Ok(loaded.unwrap_or_default())
+}
+
+fn save_journal(folder: &str, journal: &Journal) -> Result<(), AppError> {
+    let dir = history_dir(folder);
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, format!("create history dir error: {e}")))?;
+    write_json_atomic(&journal_path(folder), journal, "journal")
+}
+
+impl Journal {
+    pub fn status(&self) -> JournalStatus {
+        JournalStatus {
+            can_undo: !self.undo.is_empty(),
+            can_redo: !self.redo.is_empty(),
+            undo_count: self.undo.len(),
+            redo_count: self.redo.len(),
+            last_change_at: self.undo.last().map(|entry| normalize_timestamp(&entry.at)),
+        }
+    }
+}
+
+/// Journals the difference between two saved versions as a new edit, which clears the redo stack.
+pub fn record_edit(folder: &str, previous: &CycleData, next: &CycleData) -> Result<(), AppError> {
+    let changes = diff_cycle_data(previous, next);
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let mut journal = load_journal(folder)?;
+    journal.undo.push(JournalEntry {
+        id: uid("change"),
+        at: now_iso(),
+        changes,
+    });
+    if journal.undo.len() > MAX_JOURNAL_ENTRIES {
+        let overflow = journal.undo.len() - MAX_JOURNAL_ENTRIES;
+        journal.undo.drain(..overflow);
+    }
+    journal.redo.clear();
+    save_journal(folder, &journal)
+}
+
+fn apply_to_list<T: Serialize + DeserializeOwned>(
+    items: &[T],
+    change: &EntityChange,
+    target: Option<&Value>,
+) -> Result<Vec<T>, AppError> {
+    let mut values = items
+        .iter()
+        .map(|item| {
+            serde_json::to_value(item).map_err(|e| AppError::internal(format!("serialize journal entity error: {e}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let existing = values
+        .iter()
+        .position(|value| value.get("id").and_then(Value::as_str) == Some(change.id.as_str()));
+
+    match (target, existing) {
+        (None, Some(position)) => {
+            values.remove(position);
+        }
+        (None, None) => {}
+        (Some(value), Some(position)) => values[position] = value.clone(),
+        (Some(value), None) => {
+            let position = change.position.unwrap_or(values.len()).min(values.len());
+            values.insert(position, value.clone());
+        }
+    }
+
+    values
+        .into_iter()
+        .map(|value| {
+            serde_json::from_value(value).map_err(|e| AppError::corrupt(format!("parse journal entity error: {e}")))
+        })
+        .collect()
+}
+
+/// Replays an entry backwards (`Undo`, restoring each `before`) or forwards (`Redo`, restoring
+/// each `after`).
+fn apply_entry(data: &CycleData, entry: &JournalEntry, direction: Direction) -> Result<CycleData, AppError> {
+    let mut next = data.clone();
+    let ordered: Box<dyn Iterator<Item = &EntityChange>> = match direction {
+        Direction::Undo => Box::new(entry.changes.iter().rev()),
+        Direction::Redo => Box::new(entry.changes.iter()),
+    };
+    for change in ordered {
+        let target = match direction {
+            Direction::Undo => change.before.as_ref(),
+            Direction::Redo => change.after.as_ref(),
+        };
+        match change.kind {
+            EntityKind::Goal => next.goals = apply_to_list(&next.goals, change, target)?,
+            EntityKind::Work => next.works = apply_to_list(&next.works, change, target)?,
+            EntityKind::Task => next.tasks = apply_to_list(&next.tasks, change, target)?,
+        }
+    }
+    Ok(next)
+}
+
+/// Computes the data after undoing or redoing the latest entry. The caller persists the data and
+/// then calls `finish_step` so the journal only moves once the save succeeded.
+pub fn step(folder: &str, current: &CycleData, direction: Direction) -> Result<Option<CycleData>, AppError> {
+    let journal = load_journal(folder)?;
+    let entry = match direction {
+        Direction::Undo => journal.undo.last(),
+        Direction::Redo => journal.redo.last(),
+    };
+    entry.map(|entry| apply_entry(current, entry, direction)).transpose()
+}
+
+pub fn finish_step(folder: &str, direction: Direction) -> Result<Journal, AppError> {
+    let mut journal = load_journal(folder)?;
+    match direction {
+        Direction::Undo => {
+            if let Some(entry) = journal.undo.pop() {
+                journal.redo.push(entry);
+            }
+        }
+        Direction::Redo => {
+            if let Some(entry) = journal.redo.pop() {
+                journal.undo.push(entry);
+            }
+        }
+    }
+    save_journal(folder, &journal)?;
+    Ok(journal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cycle(tasks: Value) -> CycleData {
+        serde_json::from_value(json!({
+            "schemaVersion": 2,
+            "id": "c1",
+            "name": "Cycle",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn task(id: &str, title: &str, done: bool) -> Value {
+        json!({ "id": id, "cycleId": "c1", "workId": "w1", "title": title, "done": done })
+    }
+
+    fn as_json(data: &CycleData) -> Value {
+        serde_json::to_value(data).unwrap()
+    }
+
+    fn entry(previous: &CycleData, next: &CycleData) -> JournalEntry {
+        JournalEntry {
+            id: "change-1".to_string(),
+            at: "2026-10-17T12:00:00Z".to_string(),
+            changes: diff_cycle_data(previous, next),
+        }
+    }
+
+    fn temp_folder() -> String {
+        let folder = std::env::temp_dir().join(format!("cycle-journal-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(&folder).unwrap();
+        folder.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn undo_and_redo_replay_an_entry_both_ways() {
+        let previous = cycle(json!([task("t1", "Write", false), task("t2", "Test", false), task("t3", "Ship", false)]));
+        let next = cycle(json!([task("t1", "Write", true), task("t3", "Ship", false), task("t4", "Release", false)]));
+        let entry = entry(&previous, &next);
+
+        let undone = apply_entry(&next, &entry, Direction::Undo).unwrap();
+        assert_eq!(as_json(&undone), as_json(&previous));
+        let redone = apply_entry(&undone, &entry, Direction::Redo).unwrap();
+        assert_eq!(as_json(&redone), as_json(&next));
+    }
+
+    #[test]
+    fn undoing_a_delete_puts_the_entity_back_in_place() {
+        let previous = cycle(json!([task("t1", "Write", false), task("t2", "Test", false), task("t3", "Ship", false)]));
+        let next = cycle(json!([task("t1", "Write", false), task("t3", "Ship", false)]));
+
+        let undone = apply_entry(&next, &entry(&previous, &next), Direction::Undo).unwrap();
+        let ids = undone.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["t1", "t2", "t3"]);
+    }
+
+    #[test]
+    fn a_step_moves_between_the_stacks_only_when_finished() {
+        let folder = temp_folder();
+        let previous = cycle(json!([task("t1", "Write", false)]));
+        let next = cycle(json!([task("t1", "Write", true)]));
+        record_edit(&folder, &previous, &next).unwrap();
+
+        let undone = step(&folder, &next, Direction::Undo).unwrap().unwrap();
+        assert_eq!(as_json(&undone), as_json(&previous));
+        assert_eq!(load_journal(&folder).unwrap().status().undo_count, 1);
+
+        let status = finish_step(&folder, Direction::Undo).unwrap().status();
+        let _ = fs::remove_dir_all(&folder);
+        assert!(!status.can_undo);
+        assert_eq!(status.redo_count, 1);
+    }
+
+    #[test]
+    fn a_new_edit_drops_the_oldest_entry_past_the_cap_and_clears_redo() {
+        let folder = temp_folder();
+        let previous = cycle(json!([task("t1", "Write", false)]));
+        let next = cycle(json!([task("t1", "Write", true)]));
+        let full = Journal {
+            undo: (0..MAX_JOURNAL_ENTRIES)
+                .map(|n| JournalEntry {
+                    id: format!("change-{n}"),
+                    ..entry(&previous, &next)
+                })
+                .collect(),
+            redo: vec![entry(&next, &previous)],
+        };
+        save_journal(&folder, &full).unwrap();
+
+        record_edit(&folder, &previous, &next).unwrap();
+        let journal = load_journal(&folder).unwrap();
+        let _ = fs::remove_dir_all(&folder);
+
+        assert_eq!(journal.undo.len(), MAX_JOURNAL_ENTRIES);
+        assert_eq!(journal.undo[0].id, "change-1");
+        assert!(journal.redo.is_empty());
+    }
+
+    #[test]
+    fn an_edit_without_changes_is_not_journaled() {
+        let folder = temp_folder();
+        let data = cycle(json!([task("t1", "Write", false)]));
+        record_edit(&folder, &data, &data).unwrap();
+        let journal = load_journal(&folder).unwrap();
+        let _ = fs::remove_dir_all(&folder);
+        assert!(journal.undo.is_empty());
+    }
+}
@@ -1,13 +1,58 @@
+mod atomic_file;
+mod cycle_store;
+mod data_dir;
+mod dates;
+mod diff;
+mod duplicate;
+mod error;
+mod history;
+mod i18n;
+mod integrity;
+mod journal;
+mod merge;
+mod models;
+mod mutations;
+mod ops;
+mod rollover;
+mod scan;
+mod schema;
+mod settings;
+mod storage;
+mod watch;
+mod window_geometry;
+mod window_mode;
+
+use atomic_file::{backup_path, read_with_backup};
+use cycle_store::store_for;
+use data_dir::DataDir;
+use dates::now_iso;
+use diff::EntityChange;
+use error::{AppError, ErrorCode};
+use duplicate::DuplicateOptions;
+use history::SnapshotInfo;
+use i18n::{Locale, Text};
+use integrity::{IntegrityIssue, RepairOptions, RepairReport};
+use journal::JournalStatus;
+use merge::MergeReport;
+use models::{CycleData, CycleMeta, EntityKind, Goal, IndexData, StorageKind, Task, Work};
+use rollover::RolloverOptions;
+use scan::ScannedCycle;
+use schema::{ensure_not_newer, migrate_cycle_data, CYCLE_SCHEMA_VERSION};
+use settings::{Settings, SettingsStore, MAX_WINDOW_OPACITY, MIN_WINDOW_OPACITY, SETTINGS_CHANGED_EVENT};
+use storage::{FsStorage, Storage};
+use watch::CycleWatchers;
+use window_geometry::{MonitorArea, WindowGeometry, WindowStateStore};
+use window_mode::{WindowMode, WINDOW_MODE_CHANGED_EVENT};
 use rfd::FileDialog;
+use uuid::Uuid;
+use serde::Serialize;
 use raw_window_handle::{HasWindowHandle, RawWindowHandle};
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use tauri::menu::{Menu, MenuEvent, MenuItem};
 use tauri::tray::{MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent};
-use tauri::{LogicalSize, Manager, Size, WindowEvent};
+use tauri::{Emitter, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Position, Size, WindowEvent};
 #[cfg(target_os = "windows")]
 use windows::Win32::Foundation::COLORREF;
 #[cfg(target_os = "windows")]
@@ -18,105 +63,180 @@ use windows::Win32::UI::WindowsAndMessaging::{
     WS_EX_LAYERED,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CycleMeta {
-    id: String,
-    name: String,
-    created_at: String,
-    folder_path: String,
+#[derive(Default)]
+struct DesktopWindowState {
+    /// Only `change_window_mode` changes it, holding the lock for the whole switch.
+    mode: Mutex<WindowMode>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct IndexData {
-    cycles: Vec<CycleMeta>,
-    selected_cycle_id: Option<String>,
+/// `<prefix>_<UUIDv7>`: ids sort by creation time and stay unique across machines.
+fn uid(prefix: &str) -> String {
+    format!("{}_{}", prefix, Uuid::now_v7())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct CycleData {
-    id: String,
-    name: String,
-    created_at: String,
-    goals: Vec<Value>,
-    works: Vec<Value>,
-    tasks: Vec<Value>,
+/// Longest folder name derived from a cycle name, in characters, before the id suffix.
+const MAX_FOLDER_NAME_CHARS: usize = 60;
+
+/// Turns a cycle name into a folder name that is valid on every platform: letters and digits of
+/// any script (so Korean names survive), `-` and `_` are kept and everything else becomes `_`.
+/// Windows device names such as `CON` need no special case: `cycle_folder_name` always appends the
+/// id suffix. Must stay in sync with `sanitizeFolderName` in the web frontend.
+fn sanitize_folder_name(name: &str) -> String {
+    let replaced = name
+        .chars()
+        .map(|ch| if ch.is_alphanumeric() || ch == '-' || ch == '_' { ch } else { '_' })
+        .take(MAX_FOLDER_NAME_CHARS)
+        .collect::<String>();
+    let trimmed = replaced.trim_matches('_');
+    if trimmed.is_empty() {
+        "cycle".to_string()
+    } else {
+        trimmed.to_string()
+    }
 }
 
-const DEFAULT_WINDOW_WIDTH: f64 = 1320.0;
-const DEFAULT_WINDOW_HEIGHT: f64 = 860.0;
-const POST_IT_WINDOW_WIDTH: f64 = 390.0;
-const POST_IT_WINDOW_HEIGHT: f64 = 560.0;
-const CALENDAR_WINDOW_WIDTH: f64 = 1080.0;
-const CALENDAR_WINDOW_HEIGHT: f64 = 760.0;
+fn cycle_folder_name(name: &str, cycle_id: &str) -> String {
+    let suffix = cycle_id.chars().rev().take(6).collect::<String>().chars().rev().collect::<String>();
+    format!("{}_{}", sanitize_folder_name(name), suffix)
+}
 
-#[derive(Debug, Clone, Copy)]
-struct NormalWindowState {
-    width: f64,
-    height: f64,
-    was_maximized: bool,
+/// Picks `parent/base`, or `parent/base-2`, `parent/base-3`, ... when `taken` says that name is
+/// already in use.
+fn unique_child_path(parent: &Path, base: &str, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let mut candidate = parent.join(base);
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = parent.join(format!("{base}-{n}"));
+        n += 1;
+    }
+    candidate
 }
 
-struct DesktopWindowState {
-    post_it_mode: Mutex<bool>,
-    calendar_mode: Mutex<bool>,
-    normal_window_state: Mutex<Option<NormalWindowState>>,
-    window_opacity: Mutex<f64>,
-}
-
-impl Default for DesktopWindowState {
-    fn default() -> Self {
-        Self {
-            post_it_mode: Mutex::new(false),
-            calendar_mode: Mutex::new(false),
-            normal_window_state: Mutex::new(None),
-            window_opacity: Mutex::new(1.0),
-        }
+/// Whether `folder_name` is `base` or one of the `base-N` variants `unique_child_path` picks.
+fn is_named_after(folder_name: &str, base: &str) -> bool {
+    match folder_name.strip_prefix(base) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())),
+        None => false,
     }
 }
 
-fn now_iso() -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let ts = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
-    format!("{}", ts)
+/// The folder holding the index, after applying the `--data-dir` flag, the environment variable
+/// and portable mode (see `data_dir::resolve`).
+fn app_data_dir(app: &tauri::AppHandle) -> Result<DataDir, AppError> {
+    let base = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::internal(format!("app config dir error: {e}")))?;
+    let dir = data_dir::resolve(base.join("cycle-planner"));
+    fs::create_dir_all(&dir.path).map_err(|e| AppError::io(&dir.path, format!("create app dir error: {e}")))?;
+    Ok(dir)
 }
 
-fn uid(prefix: &str) -> String {
-    let rand = format!("{:x}", rand::random::<u64>());
-    format!("{}_{}", prefix, rand)
+/// Records `data` as the merge base, unless conflict copies are waiting to be merged against the
+/// previous base.
+fn update_sync_base(storage: &dyn Storage, cycle: &CycleMeta, data: &CycleData) -> Result<(), AppError> {
+    if storage.has_conflict_copies(&cycle.folder_path) {
+        return Ok(());
+    }
+    storage.write_sync_base(&cycle.id, data)
 }
 
-fn sanitize_folder_name(name: &str) -> String {
-    let mut out = String::new();
-    for ch in name.chars() {
-        if ch.is_alphanumeric() || ch == '-' || ch == '_' {
-            out.push(ch);
-        } else {
-            out.push('_');
+fn read_sync_base(storage: &dyn Storage, cycle: &CycleMeta) -> Result<CycleData, AppError> {
+    Ok(storage.read_sync_base(&cycle.id)?.unwrap_or_else(|| CycleData::empty(cycle)))
+}
+
+/// Only files that are still recognised as conflict copies are touched, whatever names the UI
+/// passes in.
+fn trash_conflict_copies(folder: &str, copies: &[String]) -> Result<(), AppError> {
+    for path in merge::find_conflict_copies(Path::new(folder)) {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if copies.contains(&name) {
+            trash::delete(&path).map_err(|e| AppError::io(&path, format!("Failed to move {name} to trash: {e}")))?;
         }
     }
-    let trimmed = out.trim_matches('_').to_string();
-    if trimmed.is_empty() {
-        "cycle".to_string()
-    } else {
-        trimmed
+    Ok(())
+}
+
+/// Runs once the merged data is saved: it becomes the new sync base (which `update_sync_base`
+/// skipped while the copies were still there) and the copies it covers go to the trash. Saving
+/// first means a failed save never loses the edits that only exist in a copy.
+fn retire_conflict_copies(
+    storage: &dyn Storage,
+    cycle: &CycleMeta,
+    merged: &CycleData,
+    copies: &[String],
+) -> Result<(), AppError> {
+    storage.write_sync_base(&cycle.id, merged)?;
+    trash_conflict_copies(&cycle.folder_path, copies)
+}
+
+/// The bookkeeping after a cycle was written: the sync merge base and a history snapshot.
+fn record_saved_cycle(storage: &dyn Storage, cycle: &CycleMeta, data: &CycleData) -> Result<(), AppError> {
+    update_sync_base(storage, cycle, data)?;
+    storage.record_snapshot(&cycle.folder_path, cycle.storage, data)
+}
+
+/// Writes a new version of a cycle and does the bookkeeping every save shares: revision tracking
+/// for the watcher, the sync merge base and a history snapshot. Returns the new revision and the
+/// version that was replaced.
+fn persist_cycle_data(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle: &CycleMeta,
+    data: &CycleData,
+    expected_revision: Option<String>,
+) -> Result<(Option<String>, Option<CycleData>), AppError> {
+    let folder = &cycle.folder_path;
+    let mut previous = None;
+    let current_revision = || storage.cycle_revision(folder, cycle.storage);
+    let revision = watchers.guarded_write(&cycle.id, expected_revision, current_revision, || {
+        previous = storage.read_cycle(folder, cycle.storage).ok().flatten();
+        write_cycle_data(storage, cycle, data)
+    })?;
+    record_saved_cycle(storage, cycle, data)?;
+    Ok((revision, previous))
+}
+
+/// Persists an edit and journals it so it can be undone.
+fn commit_cycle_data(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle: &CycleMeta,
+    data: &CycleData,
+    expected_revision: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let (revision, previous) = persist_cycle_data(storage, watchers, cycle, data, expected_revision)?;
+    if let Some(previous) = previous {
+        storage.record_edit(&cycle.folder_path, &previous, data)?;
     }
+    Ok(revision)
 }
 
-fn app_data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    let base = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("app config dir error: {e}"))?;
-    let dir = base.join("cycle-planner");
-    fs::create_dir_all(&dir).map_err(|e| format!("create app dir error: {e}"))?;
-    Ok(dir)
+/// Writes cycle data the app changes on its own, outside of an edit (no snapshot or journal
+/// entry), while still telling the watcher so the write is not reported as an external change.
+fn write_watched_cycle_data(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle: &CycleMeta,
+    data: &CycleData,
+) -> Result<Option<String>, AppError> {
+    let current_revision = || storage.cycle_revision(&cycle.folder_path, cycle.storage);
+    watchers.guarded_write(&cycle.id, None, current_revision, || write_cycle_data(storage, cycle, data))
 }
 
-fn index_file_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
-    Ok(app_data_dir(app)?.join("index.json"))
+/// Pins the fields of a cycle document the frontend is not allowed to change.
+fn normalize_incoming(cycle: &CycleMeta, data: CycleData) -> CycleData {
+    let mut next = data;
+    next.schema_version = CYCLE_SCHEMA_VERSION;
+    next.id = cycle.id.clone();
+    next.name = cycle.name.clone();
+    if next.created_at.is_empty() {
+        next.created_at = cycle.created_at.clone();
+    }
+    next
 }
 
 fn cycle_file_path(folder: &str) -> PathBuf {
@@ -136,48 +256,53 @@ fn normalize_display_path(path: &str) -> String {
     path.to_string()
 }
 
-fn read_index(app: &tauri::AppHandle) -> Result<IndexData, String> {
-    let path = index_file_path(app)?;
-    if !path.exists() {
-        return Ok(IndexData {
-            cycles: vec![],
-            selected_cycle_id: None,
-        });
-    }
-
-    let raw = fs::read_to_string(path).map_err(|e| format!("read index error: {e}"))?;
-    let mut index = serde_json::from_str::<IndexData>(&raw).map_err(|e| format!("parse index error: {e}"))?;
+fn read_index(storage: &dyn Storage) -> Result<IndexData, AppError> {
+    let Some(mut index) = storage.read_index()? else {
+        return Ok(IndexData::default());
+    };
     for cycle in &mut index.cycles {
         cycle.folder_path = normalize_display_path(&cycle.folder_path);
+        cycle.missing = !storage.is_dir(&cycle.folder_path);
     }
     Ok(index)
 }
 
-fn write_index(app: &tauri::AppHandle, index: &IndexData) -> Result<(), String> {
-    let path = index_file_path(app)?;
-    let raw = serde_json::to_string_pretty(index).map_err(|e| format!("serialize index error: {e}"))?;
-    fs::write(path, raw).map_err(|e| format!("write index error: {e}"))
-}
-
-fn ensure_cycle_data(cycle: &CycleMeta) -> Result<CycleData, String> {
-    let file = cycle_file_path(&cycle.folder_path);
-    if !file.exists() {
-        let data = CycleData {
-            id: cycle.id.clone(),
-            name: cycle.name.clone(),
-            created_at: cycle.created_at.clone(),
-            goals: vec![],
-            works: vec![],
-            tasks: vec![],
-        };
-        let raw = serde_json::to_string_pretty(&data).map_err(|e| format!("serialize cycle data error: {e}"))?;
-        fs::create_dir_all(Path::new(&cycle.folder_path)).map_err(|e| format!("create cycle dir error: {e}"))?;
-        fs::write(file, raw).map_err(|e| format!("write cycle data error: {e}"))?;
-        return Ok(data);
+fn write_index(storage: &dyn Storage, index: &IndexData) -> Result<(), AppError> {
+    storage.write_index(index)
+}
+
+fn read_cycle_file(file: &Path) -> Result<Option<CycleData>, AppError> {
+    ensure_not_newer(file, "cycle data", CYCLE_SCHEMA_VERSION)?;
+    read_with_backup(file, "cycle data", |raw| {
+        serde_json::from_value::<CycleData>(migrate_cycle_data(raw)?)
+            .map_err(|e| AppError::corrupt(format!("parse cycle data error: {e}")))
+    })
+}
+
+fn missing_folder_error(cycle: &CycleMeta) -> AppError {
+    let message = format!(
+        "The folder of cycle \"{}\" was not found at {}. Relocate the cycle to its new folder.",
+        cycle.name, cycle.folder_path
+    );
+    AppError::new(ErrorCode::FolderMissing, message)
+        .with_path(&cycle.folder_path)
+        .with_cycle(&cycle.id)
+}
+
+fn cycle_data_not_found(folder_path: &str) -> AppError {
+    AppError::new(ErrorCode::CycleDataNotFound, "cycle_data.json was not found.").with_path(folder_path)
+}
+
+fn ensure_cycle_data(storage: &dyn Storage, cycle: &CycleMeta) -> Result<CycleData, AppError> {
+    if !storage.is_dir(&cycle.folder_path) {
+        return Err(missing_folder_error(cycle));
     }
+    let Some(mut data) = storage.read_cycle(&cycle.folder_path, cycle.storage)? else {
+        let data = CycleData::empty(cycle);
+        write_cycle_data(storage, cycle, &data)?;
+        return Ok(data);
+    };
 
-    let raw = fs::read_to_string(&file).map_err(|e| format!("read cycle data error: {e}"))?;
-    let mut data = serde_json::from_str::<CycleData>(&raw).map_err(|e| format!("parse cycle data error: {e}"))?;
     if data.id.is_empty() {
         data.id = cycle.id.clone();
     }
@@ -190,30 +315,159 @@ fn ensure_cycle_data(cycle: &CycleMeta) -> Result<CycleData, String> {
     Ok(data)
 }
 
-fn write_cycle_data(cycle: &CycleMeta, data: &CycleData) -> Result<(), String> {
-    let file = cycle_file_path(&cycle.folder_path);
-    fs::create_dir_all(Path::new(&cycle.folder_path)).map_err(|e| format!("create cycle dir error: {e}"))?;
-    let raw = serde_json::to_string_pretty(data).map_err(|e| format!("serialize cycle data error: {e}"))?;
-    fs::write(file, raw).map_err(|e| format!("write cycle data error: {e}"))
+fn write_cycle_data(storage: &dyn Storage, cycle: &CycleMeta, data: &CycleData) -> Result<(), AppError> {
+    if !storage.is_dir(&cycle.folder_path) {
+        return Err(missing_folder_error(cycle));
+    }
+    storage.write_cycle(&cycle.folder_path, cycle.storage, data)
+}
+
+/// Creates `<parent>/<name>_<suffix>` for a new cycle and returns its (not yet registered) entry.
+fn create_cycle_folder(storage: &dyn Storage, parent_dir: &str, name: String) -> Result<CycleMeta, AppError> {
+    if !storage.is_dir(parent_dir) {
+        let message = "The selected parent folder is not valid.";
+        return Err(AppError::new(ErrorCode::InvalidFolder, message).with_path(parent_dir));
+    }
+
+    let cycle_id = uid("cycle");
+    let folder_path = unique_child_path(Path::new(parent_dir), &cycle_folder_name(&name, &cycle_id), |path| {
+        storage.is_dir(&path.to_string_lossy())
+    });
+    storage.create_dir(&folder_path.to_string_lossy())?;
+
+    Ok(CycleMeta {
+        id: cycle_id,
+        name,
+        created_at: now_iso(),
+        folder_path: normalize_display_path(&folder_path.to_string_lossy()),
+        archived: false,
+        missing: false,
+        closed_at: None,
+        storage: StorageKind::Json,
+    })
+}
+
+fn register_new_cycle(index: &mut IndexData, meta: CycleMeta) {
+    if index.selected_cycle_id.is_none() {
+        index.selected_cycle_id = Some(meta.id.clone());
+    }
+    index.cycles.push(meta);
+}
+
+/// Reads the cycle data in a folder and the storage kind it uses, or `None` when there is none.
+fn read_folder_data(
+    storage: &dyn Storage,
+    folder_path: &str,
+) -> Result<Option<(CycleData, StorageKind)>, AppError> {
+    let Some(kind) = storage.detect_cycle(folder_path) else {
+        return Ok(None);
+    };
+    Ok(storage.read_cycle(folder_path, kind)?.map(|data| (data, kind)))
+}
+
+/// Reads the cycle data from a folder being imported, filling in fields older files may lack.
+fn read_import_data(storage: &dyn Storage, folder_path: &str) -> Result<(CycleData, StorageKind), AppError> {
+    let (mut data, kind) =
+        read_folder_data(storage, folder_path)?.ok_or_else(|| cycle_data_not_found(folder_path))?;
+
+    if data.id.is_empty() {
+        data.id = uid("cycle");
+    }
+    if data.name.is_empty() {
+        data.name = "Imported Cycle".to_string();
+    }
+    if data.created_at.is_empty() {
+        data.created_at = now_iso();
+    }
+    Ok((data, kind))
+}
+
+/// Adds or refreshes the index entry for an imported cycle and returns it.
+fn register_imported_cycle(
+    index: &mut IndexData,
+    data: &CycleData,
+    storage: StorageKind,
+    folder_path: &str,
+) -> CycleMeta {
+    let folder_path = normalize_display_path(folder_path);
+    if let Some(existing) = index.cycles.iter_mut().find(|c| c.id == data.id) {
+        existing.name = data.name.clone();
+        existing.folder_path = folder_path;
+        existing.created_at = data.created_at.clone();
+        existing.missing = false;
+        existing.storage = storage;
+        return existing.clone();
+    }
+
+    let meta = CycleMeta {
+        id: data.id.clone(),
+        name: data.name.clone(),
+        created_at: data.created_at.clone(),
+        folder_path,
+        archived: false,
+        missing: false,
+        closed_at: None,
+        storage,
+    };
+    index.cycles.push(meta.clone());
+    meta
+}
+
+fn upsert_by_id<T>(items: &mut Vec<T>, item: T, id: impl Fn(&T) -> &String) {
+    match items.iter().position(|existing| id(existing) == id(&item)) {
+        Some(position) => items[position] = item,
+        None => items.push(item),
+    }
 }
 
 fn find_cycle(index: &IndexData, cycle_id: &str) -> Option<CycleMeta> {
     index.cycles.iter().find(|c| c.id == cycle_id).cloned()
 }
 
-fn main_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWindow, String> {
+/// Moves the selection off `cycle_id` (after it was removed or archived) to the first cycle that
+/// is still visible in the selector.
+fn reselect_away_from(index: &mut IndexData, cycle_id: &str) {
+    if index.selected_cycle_id.as_deref() != Some(cycle_id) {
+        return;
+    }
+    index.selected_cycle_id = index
+        .cycles
+        .iter()
+        .find(|c| c.id != cycle_id && !c.archived)
+        .map(|c| c.id.clone());
+}
+
+fn remove_cycle_entry(index: &mut IndexData, cycle_id: &str) -> Result<CycleMeta, AppError> {
+    let position = index
+        .cycles
+        .iter()
+        .position(|c| c.id == cycle_id)
+        .ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let removed = index.cycles.remove(position);
+    reselect_away_from(index, cycle_id);
+    Ok(removed)
+}
+
+const TRAY_ID: &str = "main";
+
+fn tray_menu(app: &tauri::AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let quit_item = MenuItem::with_id(app, "quit", i18n::text(i18n::current(), Text::TrayQuit), true, None::<&str>)?;
+    Menu::with_items(app, &[&quit_item])
+}
+
+fn main_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWindow, AppError> {
     app.get_webview_window("main")
-        .ok_or_else(|| "Main window was not found.".to_string())
+        .ok_or_else(|| AppError::window("Main window was not found."))
 }
 
 #[cfg(target_os = "windows")]
-fn apply_window_opacity(window: &tauri::WebviewWindow, opacity: f64) -> Result<(), String> {
+fn apply_window_opacity(window: &tauri::WebviewWindow, opacity: f64) -> Result<(), AppError> {
     let window_handle = window
         .window_handle()
-        .map_err(|e| format!("Failed to get window handle: {e}"))?;
+        .map_err(|e| AppError::window(format!("Failed to get window handle: {e}")))?;
     let hwnd = match window_handle.as_raw() {
         RawWindowHandle::Win32(handle) => HWND(handle.hwnd.get() as _),
-        _ => return Err("Unsupported window handle type for opacity.".to_string()),
+        _ => return Err(AppError::window("Unsupported window handle type for opacity.")),
     };
     let alpha = (opacity * 255.0).round() as u8;
     unsafe {
@@ -222,7 +476,7 @@ fn apply_window_opacity(window: &tauri::WebviewWindow, opacity: f64) -> Result<(
             SetWindowLongPtrW(hwnd, GWL_EXSTYLE, (current_exstyle | WS_EX_LAYERED.0) as _);
         }
         SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA)
-            .map_err(|e| format!("Failed to apply window opacity: {e}"))?;
+            .map_err(|e| AppError::window(format!("Failed to apply window opacity: {e}")))?;
     }
     Ok(())
 }
@@ -239,211 +493,1021 @@ fn pick_folder() -> Option<String> {
 }
 
 #[tauri::command]
-fn load_index(app: tauri::AppHandle) -> Result<IndexData, String> {
-    read_index(&app)
+fn load_index(storage: tauri::State<FsStorage>) -> Result<IndexData, AppError> {
+    ops::load_index(storage.inner())
 }
 
 #[tauri::command]
 #[allow(non_snake_case)]
-fn select_cycle(app: tauri::AppHandle, cycleId: String) -> Result<IndexData, String> {
-    let mut index = read_index(&app)?;
-    if find_cycle(&index, &cycleId).is_none() {
-        return Err("Selected Cycle does not exist.".to_string());
+fn select_cycle(storage: tauri::State<FsStorage>, cycleId: String) -> Result<IndexData, AppError> {
+    ops::select_cycle(storage.inner(), &cycleId)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn create_cycle(storage: tauri::State<FsStorage>, name: String, parentDir: String) -> Result<IndexData, AppError> {
+    ops::create_cycle(storage.inner(), name, &parentDir)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn duplicate_cycle(
+    storage: tauri::State<FsStorage>,
+    sourceId: String,
+    name: String,
+    parentDir: String,
+    options: DuplicateOptions,
+) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage.inner())?;
+    let source = find_cycle(&index, &sourceId).ok_or_else(|| AppError::cycle_not_found(&sourceId))?;
+    let source_data = ensure_cycle_data(storage.inner(), &source)?;
+
+    let meta = create_cycle_folder(storage.inner(), &parentDir, name)?;
+    let folder = meta.folder_path.clone();
+    let data = duplicate::duplicate_cycle_data(&source_data, &meta, &options);
+
+    let result = write_cycle_data(storage.inner(), &meta, &data).and_then(|_| {
+        register_new_cycle(&mut index, meta);
+        write_index(storage.inner(), &index)
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&folder);
+        return Err(e);
     }
-    index.selected_cycle_id = Some(cycleId);
-    write_index(&app, &index)?;
     Ok(index)
 }
 
 #[tauri::command]
 #[allow(non_snake_case)]
-fn create_cycle(app: tauri::AppHandle, name: String, parentDir: String) -> Result<IndexData, String> {
-    let parent = Path::new(&parentDir);
-    if !parent.exists() || !parent.is_dir() {
-        return Err("The selected parent folder is not valid.".to_string());
+fn rollover_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    sourceId: String,
+    name: String,
+    parentDir: String,
+    options: RolloverOptions,
+) -> Result<IndexData, AppError> {
+    ops::rollover_cycle(storage.inner(), &watchers, &sourceId, name, &parentDir, &options)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn import_cycle(storage: tauri::State<FsStorage>, folderPath: String) -> Result<IndexData, AppError> {
+    ops::import_cycle(storage.inner(), &folderPath)
+}
+
+#[tauri::command]
+fn scan_for_cycles(
+    storage: tauri::State<FsStorage>,
+    root: String,
+    depth: u32,
+) -> Result<Vec<ScannedCycle>, AppError> {
+    let index = read_index(storage.inner())?;
+    scan::scan_for_cycles(storage.inner(), Path::new(&root), depth, &index)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn import_cycles(storage: tauri::State<FsStorage>, folderPaths: Vec<String>) -> Result<IndexData, AppError> {
+    ops::import_cycles(storage.inner(), &folderPaths)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn rename_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    name: String,
+    renameFolder: bool,
+) -> Result<IndexData, AppError> {
+    ops::rename_cycle(storage.inner(), &watchers, &cycleId, &name, renameFolder)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn relocate_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    folderPath: String,
+) -> Result<IndexData, AppError> {
+    ops::relocate_cycle(storage.inner(), &watchers, &cycleId, &folderPath)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn delete_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    trashFolder: bool,
+) -> Result<IndexData, AppError> {
+    ops::delete_cycle(storage.inner(), &watchers, &cycleId, trashFolder)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn unregister_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+) -> Result<IndexData, AppError> {
+    ops::unregister_cycle(storage.inner(), &watchers, &cycleId)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn archive_cycle(storage: tauri::State<FsStorage>, cycleId: String, archived: bool) -> Result<IndexData, AppError> {
+    ops::archive_cycle(storage.inner(), &cycleId, archived)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn load_cycle_data(
+    app: tauri::AppHandle,
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+) -> Result<CycleData, AppError> {
+    let data = ops::load_cycle_data(storage.inner(), &cycleId)?;
+    // Live reload is best effort; a folder that cannot be watched still loads and saves.
+    if let Some(cycle) = find_cycle(&read_index(storage.inner())?, &cycleId) {
+        let _ = watchers.watch(&app, &cycle);
     }
+    Ok(data)
+}
 
-    let mut index = read_index(&app)?;
-    let cycle_id = uid("cycle");
-    let suffix = cycle_id.chars().rev().take(6).collect::<String>().chars().rev().collect::<String>();
-    let folder_name = format!("{}_{}", sanitize_folder_name(&name), suffix);
-    let folder_path = parent.join(folder_name);
-    fs::create_dir_all(&folder_path).map_err(|e| format!("Failed to create cycle folder: {e}"))?;
+/// Content hash of the cycle's data file (`cycle_data.json` or `cycle_data.sqlite`) as it is on
+/// disk now, for `save_cycle_data`'s `expectedRevision`.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn get_cycle_revision(storage: tauri::State<FsStorage>, cycleId: String) -> Result<Option<String>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    Ok(storage.cycle_revision(&cycle.folder_path, cycle.storage))
+}
 
-    let meta = CycleMeta {
-        id: cycle_id.clone(),
-        name,
-        created_at: now_iso(),
-        folder_path: normalize_display_path(&folder_path.to_string_lossy()),
-    };
+fn same_cycle_data(a: &CycleData, b: &CycleData) -> bool {
+    matches!((serde_json::to_value(a), serde_json::to_value(b)), (Ok(a), Ok(b)) if a == b)
+}
 
-    let data = CycleData {
-        id: meta.id.clone(),
-        name: meta.name.clone(),
-        created_at: meta.created_at.clone(),
-        goals: vec![],
-        works: vec![],
-        tasks: vec![],
-    };
+/// Moves a cycle's data to `kind` (JSON file or SQLite database) without losing anything: the
+/// new copy is read back and compared before the index switches to it and the old file is
+/// moved to the trash.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn convert_cycle_storage(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    kind: StorageKind,
+) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    if cycle.storage == kind {
+        return Ok(index);
+    }
+    let data = ensure_cycle_data(storage.inner(), &cycle)?;
+
+    let folder = Path::new(&cycle.folder_path);
+    let (source, target) = (store_for(cycle.storage), store_for(kind));
+    let target_file = target.data_file(folder);
+    if target_file.exists() {
+        let message = format!(
+            "{} already exists in the cycle folder. Move it away before converting.",
+            target_file.display()
+        );
+        return Err(AppError::new(ErrorCode::InvalidFolder, message).with_path(&target_file).with_cycle(&cycleId));
+    }
 
-    write_cycle_data(&meta, &data)?;
-    index.cycles.push(meta);
-    if index.selected_cycle_id.is_none() {
-        index.selected_cycle_id = Some(cycle_id);
+    let converted = target
+        .write(folder, &data)
+        .and_then(|_| target.read(folder))
+        .and_then(|written| match written {
+            Some(written) if same_cycle_data(&written, &data) => Ok(()),
+            _ => Err(AppError::internal("The converted cycle data does not match the original.")),
+        });
+    let switched = converted.and_then(|_| {
+        if let Some(entry) = index.cycles.iter_mut().find(|c| c.id == cycleId) {
+            entry.storage = kind;
+        }
+        write_index(storage.inner(), &index)
+    });
+    if let Err(e) = switched {
+        let _ = fs::remove_file(&target_file);
+        return Err(e);
+    }
+
+    watchers.unwatch(&cycleId);
+    let source_file = source.data_file(folder);
+    for old in [backup_path(&source_file), source_file] {
+        if old.exists() {
+            trash::delete(&old)
+                .map_err(|e| AppError::io(&old, format!("Failed to move {} to trash: {e}", old.display())))?;
+        }
     }
-    write_index(&app, &index)?;
     Ok(index)
 }
 
 #[tauri::command]
 #[allow(non_snake_case)]
-fn import_cycle(app: tauri::AppHandle, folderPath: String) -> Result<IndexData, String> {
-    let file = cycle_file_path(&folderPath);
-    if !file.exists() {
-        return Err("cycle_data.json was not found.".to_string());
+fn unwatch_cycle(watchers: tauri::State<CycleWatchers>, cycleId: String) {
+    watchers.unwatch(&cycleId);
+}
+
+/// Saves the cycle and returns its new revision. When `expectedRevision` is given and the file
+/// changed on disk since then, the save is rejected instead of clobbering the external edit.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn save_cycle_data(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    data: CycleData,
+    expectedRevision: Option<String>,
+) -> Result<Option<String>, AppError> {
+    ops::save_cycle_data(storage.inner(), &watchers, &cycleId, data, expectedRevision)
+}
+
+/// File names of sync-client conflict copies sitting next to the cycle's `cycle_data.json`.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn list_conflict_copies(storage: tauri::State<FsStorage>, cycleId: String) -> Result<Vec<String>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    Ok(merge::find_conflict_copies(Path::new(&cycle.folder_path))
+        .iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .collect())
+}
+
+/// Three-way merges every conflict copy into the cycle. Without conflicts the result is saved
+/// and the copies are moved to the trash; otherwise nothing is written and the UI resolves the
+/// returned conflicts through `resolve_conflict_copies`.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn merge_conflict_copies(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+) -> Result<MergeReport, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let base = read_sync_base(storage.inner(), &cycle)?;
+    let mut merged = ensure_cycle_data(storage.inner(), &cycle)?;
+
+    let mut conflicts = vec![];
+    let mut copies = vec![];
+    for path in merge::find_conflict_copies(Path::new(&cycle.folder_path)) {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let theirs = read_cycle_file(&path)?
+            .ok_or_else(|| AppError::not_found(format!("{name} was not found.")).with_path(&path))?;
+        merged = merge::merge_cycle_data(&base, &merged, &theirs, &name, &mut conflicts)?;
+        copies.push(name);
     }
 
-    let raw = fs::read_to_string(&file).map_err(|e| format!("read import cycle data error: {e}"))?;
-    let mut data = serde_json::from_str::<CycleData>(&raw).map_err(|e| format!("parse import cycle data error: {e}"))?;
+    let applied = !copies.is_empty() && conflicts.is_empty();
+    if applied {
+        commit_cycle_data(storage.inner(), &watchers, &cycle, &merged, None)?;
+        retire_conflict_copies(storage.inner(), &cycle, &merged, &copies)?;
+    }
+    Ok(MergeReport {
+        merged,
+        conflicts,
+        copies,
+        applied,
+    })
+}
 
-    if data.id.is_empty() {
-        data.id = uid("cycle");
+/// Saves the merge the user finished resolving and retires the conflict copies it covered.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn resolve_conflict_copies(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    data: CycleData,
+    copies: Vec<String>,
+) -> Result<Option<String>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let next = normalize_incoming(&cycle, data);
+    let revision = commit_cycle_data(storage.inner(), &watchers, &cycle, &next, None)?;
+    retire_conflict_copies(storage.inner(), &cycle, &next, &copies)?;
+    Ok(revision)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MutationResult<T> {
+    value: T,
+    /// Revision of the saved data, for the next mutation's `expectedRevision`.
+    revision: Option<String>,
+}
+
+/// Loads the cycle, applies one entity-level change and commits it like a regular save. Like
+/// `save_cycle_data`, it is refused when the data changed since the client's `expected_revision`.
+fn mutate_cycle<T>(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    expected_revision: Option<String>,
+    mutation: impl FnOnce(&mut CycleData) -> Result<T, AppError>,
+) -> Result<MutationResult<T>, AppError> {
+    let index = read_index(storage)?;
+    let cycle = find_cycle(&index, cycle_id).ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let mut data = ensure_cycle_data(storage, &cycle)?;
+    let value = mutation(&mut data)?;
+    let revision = commit_cycle_data(storage, watchers, &cycle, &data, expected_revision)?;
+    Ok(MutationResult { value, revision })
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn upsert_goal(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    goal: Goal,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Goal>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::upsert_goal(data, goal))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn upsert_work(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    work: Work,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Work>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::upsert_work(data, work))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn upsert_task(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    task: Task,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Task>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::upsert_task(data, task))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn delete_goal(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    goalId: String,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Goal>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::delete_goal(data, &goalId))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn delete_work(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    workId: String,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Work>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::delete_work(data, &workId))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn delete_task(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    taskId: String,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Task>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::delete_task(data, &taskId))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn toggle_task(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    taskId: String,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Task>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| mutations::toggle_task(data, &taskId))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn move_work_to_goal(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    workId: String,
+    goalId: Option<String>,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<Work>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| {
+        mutations::move_work_to_goal(data, &workId, goalId)
+    })
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn validate_cycle(storage: tauri::State<FsStorage>, cycleId: String) -> Result<Vec<IntegrityIssue>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let data = ensure_cycle_data(storage.inner(), &cycle)?;
+    Ok(integrity::validate_cycle_data(&data))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn repair_cycle(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    options: RepairOptions,
+    expectedRevision: Option<String>,
+) -> Result<MutationResult<RepairReport>, AppError> {
+    mutate_cycle(storage.inner(), &watchers, &cycleId, expectedRevision, |data| {
+        let before = integrity::validate_cycle_data(data).len();
+        integrity::repair_cycle_data(data, options);
+        let remaining = integrity::validate_cycle_data(data);
+        Ok(RepairReport {
+            data: data.clone(),
+            fixed: before.saturating_sub(remaining.len()),
+            remaining,
+        })
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalStepResult {
+    data: CycleData,
+    status: JournalStatus,
+}
+
+fn journal_step(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    direction: journal::Direction,
+) -> Result<JournalStepResult, AppError> {
+    let index = read_index(storage)?;
+    let cycle = find_cycle(&index, cycle_id).ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let current = ensure_cycle_data(storage, &cycle)?;
+    let data = journal::step(&cycle.folder_path, &current, direction)?.ok_or_else(|| match direction {
+        journal::Direction::Undo => AppError::invalid_input("There is nothing to undo."),
+        journal::Direction::Redo => AppError::invalid_input("There is nothing to redo."),
+    })?;
+    persist_cycle_data(storage, watchers, &cycle, &data, None)?;
+    let journal = journal::finish_step(&cycle.folder_path, direction)?;
+    Ok(JournalStepResult {
+        data,
+        status: journal.status(),
+    })
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn undo(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+) -> Result<JournalStepResult, AppError> {
+    journal_step(storage.inner(), &watchers, &cycleId, journal::Direction::Undo)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn redo(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+) -> Result<JournalStepResult, AppError> {
+    journal_step(storage.inner(), &watchers, &cycleId, journal::Direction::Redo)
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn journal_status(storage: tauri::State<FsStorage>, cycleId: String) -> Result<JournalStatus, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    Ok(journal::load_journal(&cycle.folder_path)?.status())
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn list_snapshots(storage: tauri::State<FsStorage>, cycleId: String) -> Result<Vec<SnapshotInfo>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    Ok(history::list_snapshots(&cycle.folder_path))
+}
+
+/// Changes from the snapshot to the current data.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn diff_snapshot(
+    storage: tauri::State<FsStorage>,
+    cycleId: String,
+    snapshotId: String,
+) -> Result<Vec<EntityChange>, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let snapshot = history::read_snapshot(&cycle.folder_path, &snapshotId)?;
+    let current = ensure_cycle_data(storage.inner(), &cycle)?;
+    Ok(diff::diff_cycle_data(&snapshot, &current))
+}
+
+#[tauri::command]
+#[allow(non_snake_case)]
+fn restore_snapshot(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    snapshotId: String,
+) -> Result<CycleData, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let snapshot = history::read_snapshot(&cycle.folder_path, &snapshotId)?;
+    let next = normalize_incoming(&cycle, snapshot);
+    commit_cycle_data(storage.inner(), &watchers, &cycle, &next, None)?;
+    Ok(next)
+}
+
+/// Puts back a single goal, work or task as it was in the snapshot, leaving everything else as is.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn restore_snapshot_entity(
+    storage: tauri::State<FsStorage>,
+    watchers: tauri::State<CycleWatchers>,
+    cycleId: String,
+    snapshotId: String,
+    kind: EntityKind,
+    entityId: String,
+) -> Result<CycleData, AppError> {
+    let index = read_index(storage.inner())?;
+    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| AppError::cycle_not_found(&cycleId))?;
+    let snapshot = history::read_snapshot(&cycle.folder_path, &snapshotId)?;
+    let mut next = ensure_cycle_data(storage.inner(), &cycle)?;
+    let not_in_snapshot = || AppError::not_found("The item does not exist in this snapshot.");
+
+    match kind {
+        EntityKind::Goal => {
+            let goal = snapshot.goals.iter().find(|g| g.id == entityId).ok_or_else(not_in_snapshot)?;
+            upsert_by_id(&mut next.goals, goal.clone(), |g| &g.id);
+        }
+        EntityKind::Work => {
+            let work = snapshot.works.iter().find(|w| w.id == entityId).ok_or_else(not_in_snapshot)?;
+            upsert_by_id(&mut next.works, work.clone(), |w| &w.id);
+        }
+        EntityKind::Task => {
+            let task = snapshot.tasks.iter().find(|t| t.id == entityId).ok_or_else(not_in_snapshot)?;
+            if !next.works.iter().any(|w| w.id == task.work_id) {
+                return Err(AppError::not_found("The Work of this Task no longer exists. Restore the Work first."));
+            }
+            upsert_by_id(&mut next.tasks, task.clone(), |t| &t.id);
+        }
     }
-    if data.name.is_empty() {
-        data.name = "Imported Cycle".to_string();
+
+    commit_cycle_data(storage.inner(), &watchers, &cycle, &next, None)?;
+    Ok(next)
+}
+
+/// Where the index lives and which override (if any) chose that folder.
+#[tauri::command]
+fn get_data_dir(location: tauri::State<DataDir>) -> DataDir {
+    location.inner().clone()
+}
+
+/// Copies the index and merge bases from `sourceDir` (by default the per-user folder used without
+/// an override) into the current data folder and loads the result.
+#[tauri::command]
+#[allow(non_snake_case)]
+fn migrate_index(
+    app: tauri::AppHandle,
+    storage: tauri::State<FsStorage>,
+    settings: tauri::State<SettingsStore>,
+    geometries: tauri::State<WindowStateStore>,
+    watchers: tauri::State<CycleWatchers>,
+    location: tauri::State<DataDir>,
+    sourceDir: Option<String>,
+) -> Result<IndexData, AppError> {
+    let source = sourceDir.map(PathBuf::from).unwrap_or_else(|| location.default_path.clone());
+    data_dir::migrate(&source, storage.root())?;
+
+    // Everything held in memory came from the folder before the migration.
+    geometries.reload();
+    watchers.unwatch_all();
+
+    let previous = settings.get()?;
+    let migrated = settings.reload();
+    apply_settings(&app, &previous, &migrated)?;
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &migrated);
+    read_index(storage.inner())
+}
+
+/// Language of the messages the backend produces (errors, tray menu).
+#[tauri::command]
+fn get_locale() -> Locale {
+    i18n::current()
+}
+
+/// Switches the backend messages and the tray menu to `locale`, `None` following the system
+/// locale. Returns the language now in use.
+fn apply_locale(app: &tauri::AppHandle, locale: Option<Locale>) -> Result<Locale, AppError> {
+    let current = locale.unwrap_or_else(i18n::system_locale);
+    i18n::set_current(current);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let menu = tray_menu(app).map_err(|e| AppError::window(format!("Failed to rebuild tray menu: {e}")))?;
+        tray.set_menu(Some(menu))
+            .map_err(|e| AppError::window(format!("Failed to update tray menu: {e}")))?;
     }
-    if data.created_at.is_empty() {
-        data.created_at = now_iso();
+    Ok(current)
+}
+
+/// Saves the language choice and switches to it. Returns the language now in use.
+#[tauri::command]
+fn set_locale(
+    app: tauri::AppHandle,
+    settings: tauri::State<SettingsStore>,
+    locale: Option<Locale>,
+) -> Result<Locale, AppError> {
+    settings.ensure_writable()?;
+    let previous = settings.get()?.locale;
+    let current = apply_locale(&app, locale)?;
+    if let Err(e) = save_settings(&app, &settings, |settings| settings.locale = locale) {
+        let _ = apply_locale(&app, previous);
+        return Err(e);
     }
+    Ok(current)
+}
 
-    let mut index = read_index(&app)?;
-    if let Some(existing) = index.cycles.iter_mut().find(|c| c.id == data.id) {
-        existing.name = data.name.clone();
-        existing.folder_path = normalize_display_path(&folderPath);
-        existing.created_at = data.created_at.clone();
-    } else {
-        index.cycles.push(CycleMeta {
-            id: data.id.clone(),
-            name: data.name.clone(),
-            created_at: data.created_at.clone(),
-            folder_path: normalize_display_path(&folderPath),
-        });
+/// Saves a settings change and tells the frontend about it.
+fn save_settings(
+    app: &tauri::AppHandle,
+    settings: &SettingsStore,
+    change: impl FnOnce(&mut Settings),
+) -> Result<Settings, AppError> {
+    let next = settings.update(change)?;
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &next);
+    Ok(next)
+}
+
+/// Keeps the window above others when the setting asks for it or the mode requires it.
+fn apply_always_on_top(window: &tauri::WebviewWindow, mode: WindowMode, always_on_top: bool) -> Result<(), AppError> {
+    window
+        .set_always_on_top(mode.effective_always_on_top(always_on_top))
+        .map_err(|e| AppError::window(format!("Failed to set always-on-top state: {e}")))
+}
+
+/// Moves the window from its current mode into `mode`: remembers the geometry of the mode it
+/// leaves, restores the one of `mode`, reapplies always-on-top and opacity, saves the mode and
+/// tells the frontend. Every mode switch goes through here.
+fn change_window_mode(app: &tauri::AppHandle, mode: WindowMode) -> Result<WindowMode, AppError> {
+    let state = app.state::<DesktopWindowState>();
+    let mut current = state
+        .mode
+        .lock()
+        .map_err(|_| AppError::window("Failed to update window mode state."))?;
+    if *current == mode {
+        return Ok(mode);
     }
+    let settings = app.state::<SettingsStore>();
+    settings.ensure_writable()?;
 
-    index.selected_cycle_id = Some(data.id.clone());
+    let window = main_window(app)?;
+    switch_window_geometry(app, &window, *current, mode)?;
+    let saved = settings.get()?;
+    apply_always_on_top(&window, mode, saved.always_on_top)?;
 
-    let selected = find_cycle(&index, &data.id).ok_or_else(|| "Failed to register cycle.".to_string())?;
-    write_cycle_data(&selected, &data)?;
-    write_index(&app, &index)?;
-    Ok(index)
+    #[cfg(target_os = "windows")]
+    apply_window_opacity(&window, saved.window_opacity)?;
+
+    *current = mode;
+    drop(current);
+    save_settings(app, &settings, |settings| settings.window_mode = mode)?;
+    let _ = app.emit(WINDOW_MODE_CHANGED_EVENT, mode);
+    Ok(mode)
+}
+
+/// Makes the app match `next` where it differs from `previous`.
+fn apply_settings(app: &tauri::AppHandle, previous: &Settings, next: &Settings) -> Result<(), AppError> {
+    if next.locale != previous.locale {
+        apply_locale(app, next.locale)?;
+    }
+    let window = main_window(app)?;
+    if next.window_mode != previous.window_mode {
+        change_window_mode(app, next.window_mode)?;
+    } else if next.always_on_top != previous.always_on_top {
+        apply_always_on_top(&window, next.window_mode, next.always_on_top)?;
+    }
+
+    #[cfg(target_os = "windows")]
+    if next.window_opacity != previous.window_opacity {
+        apply_window_opacity(&window, next.window_opacity)?;
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
-#[allow(non_snake_case)]
-fn load_cycle_data(app: tauri::AppHandle, cycleId: String) -> Result<CycleData, String> {
-    let index = read_index(&app)?;
-    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| "Cycle was not found.".to_string())?;
-    ensure_cycle_data(&cycle)
+fn get_settings(settings: tauri::State<SettingsStore>) -> Result<Settings, AppError> {
+    settings.get()
 }
 
+/// Merges `patch` into the settings (a JSON merge patch: `null` resets a field to its default),
+/// applies what changed and saves them. Invalid values are rejected before anything changes, and
+/// the app is switched back when applying or saving fails, so the file never holds settings the
+/// app is not running with.
 #[tauri::command]
-#[allow(non_snake_case)]
-fn save_cycle_data(app: tauri::AppHandle, cycleId: String, data: CycleData) -> Result<(), String> {
-    let index = read_index(&app)?;
-    let cycle = find_cycle(&index, &cycleId).ok_or_else(|| "Cycle was not found.".to_string())?;
-    let mut next = data;
-    next.id = cycle.id.clone();
-    next.name = cycle.name.clone();
-    if next.created_at.is_empty() {
-        next.created_at = cycle.created_at.clone();
+fn update_settings(
+    app: tauri::AppHandle,
+    settings: tauri::State<SettingsStore>,
+    patch: serde_json::Value,
+) -> Result<Settings, AppError> {
+    settings.ensure_writable()?;
+    let previous = settings.get()?;
+    let next = settings.preview_patch(&patch)?;
+    let saved = apply_settings(&app, &previous, &next).and_then(|_| settings.apply_patch(&patch));
+    let current = match saved {
+        Ok(current) => current,
+        Err(e) => {
+            let _ = apply_settings(&app, &next, &previous);
+            return Err(e);
+        }
+    };
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &current);
+    Ok(current)
+}
+
+/// Reapplies the saved window geometry and preferences at startup.
+fn restore_window_settings(app: &tauri::AppHandle) -> Result<(), AppError> {
+    let saved = app.state::<SettingsStore>().get()?;
+    let window = main_window(app)?;
+    if let Some(geometry) = app.state::<WindowStateStore>().get(WindowMode::Normal.key()) {
+        apply_window_geometry(&window, &geometry)?;
+    }
+    apply_always_on_top(&window, WindowMode::Normal, saved.always_on_top)?;
+    change_window_mode(app, saved.window_mode)?;
+
+    #[cfg(target_os = "windows")]
+    apply_window_opacity(&window, saved.window_opacity)?;
+
+    Ok(())
+}
+
+/// The mode the window is in, or `None` while a mode switch is in progress.
+fn current_window_mode(state: &DesktopWindowState) -> Option<WindowMode> {
+    state.mode.try_lock().ok().map(|mode| *mode)
+}
+
+/// Usable area of every connected monitor, the primary one first.
+fn monitor_areas(window: &tauri::WebviewWindow) -> Vec<MonitorArea> {
+    let primary = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+    let mut areas = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let area = monitor.work_area();
+            MonitorArea {
+                name: monitor.name().cloned(),
+                x: area.position.x,
+                y: area.position.y,
+                width: area.size.width,
+                height: area.size.height,
+            }
+        })
+        .collect::<Vec<_>>();
+    areas.sort_by_key(|area| area.name != primary);
+    areas
+}
+
+/// Where the window is now, or `None` while it is minimized. A maximized window keeps the bounds
+/// of `previous`, which are where it goes when it is restored.
+fn current_geometry(
+    window: &tauri::WebviewWindow,
+    previous: Option<WindowGeometry>,
+) -> Result<Option<WindowGeometry>, AppError> {
+    let minimized = window
+        .is_minimized()
+        .map_err(|e| AppError::window(format!("Failed to read window state: {e}")))?;
+    if minimized {
+        return Ok(None);
+    }
+    let maximized = window
+        .is_maximized()
+        .map_err(|e| AppError::window(format!("Failed to read window state: {e}")))?;
+    if let (true, Some(previous)) = (maximized, previous) {
+        return Ok(Some(WindowGeometry { maximized, ..previous }));
+    }
+    let position = window
+        .outer_position()
+        .map_err(|e| AppError::window(format!("Failed to read window position: {e}")))?;
+    let size = window
+        .inner_size()
+        .map_err(|e| AppError::window(format!("Failed to read window size: {e}")))?;
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+    Ok(Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+        monitor,
+    }))
+}
+
+fn remember_window_geometry(app: &tauri::AppHandle, mode: WindowMode) -> Result<(), AppError> {
+    let Some(geometries) = app.try_state::<WindowStateStore>() else {
+        return Ok(());
+    };
+    let window = main_window(app)?;
+    if let Some(geometry) = current_geometry(&window, geometries.get(mode.key()))? {
+        geometries.remember(mode.key(), geometry);
     }
-    write_cycle_data(&cycle, &next)
+    Ok(())
+}
+
+/// Writes the geometry of the current mode to disk, when the window is hidden or the app quits.
+fn save_window_geometry(app: &tauri::AppHandle) {
+    if let Some(mode) = current_window_mode(&app.state::<DesktopWindowState>()) {
+        let _ = remember_window_geometry(app, mode);
+    }
+    if let Some(geometries) = app.try_state::<WindowStateStore>() {
+        let _ = geometries.save();
+    }
+}
+
+/// Moves the window to `geometry`, brought onto a connected monitor first.
+fn apply_window_geometry(window: &tauri::WebviewWindow, geometry: &WindowGeometry) -> Result<(), AppError> {
+    let geometry = geometry.clamped_to(&monitor_areas(window));
+    let is_maximized = window
+        .is_maximized()
+        .map_err(|e| AppError::window(format!("Failed to read window state: {e}")))?;
+    if is_maximized {
+        window
+            .unmaximize()
+            .map_err(|e| AppError::window(format!("Failed to restore window from maximized state: {e}")))?;
+    }
+    window
+        .set_size(Size::Physical(PhysicalSize::new(geometry.width, geometry.height)))
+        .map_err(|e| AppError::window(format!("Failed to restore window size: {e}")))?;
+    window
+        .set_position(Position::Physical(PhysicalPosition::new(geometry.x, geometry.y)))
+        .map_err(|e| AppError::window(format!("Failed to restore window position: {e}")))?;
+    if geometry.maximized {
+        window
+            .maximize()
+            .map_err(|e| AppError::window(format!("Failed to re-maximize window: {e}")))?;
+    }
+    Ok(())
+}
+
+/// Remembers where the window is for the mode it leaves and moves it to where mode `to` was last
+/// left, or to the mode's default size the first time it is used.
+fn switch_window_geometry(
+    app: &tauri::AppHandle,
+    window: &tauri::WebviewWindow,
+    from: WindowMode,
+    to: WindowMode,
+) -> Result<(), AppError> {
+    remember_window_geometry(app, from)?;
+    match app.state::<WindowStateStore>().get(to.key()) {
+        Some(geometry) => apply_window_geometry(window, &geometry)?,
+        None => {
+            let is_maximized = window
+                .is_maximized()
+                .map_err(|e| AppError::window(format!("Failed to read window state: {e}")))?;
+            if is_maximized {
+                window
+                    .unmaximize()
+                    .map_err(|e| AppError::window(format!("Failed to restore window from maximized state: {e}")))?;
+            }
+            let (width, height) = to.default_size();
+            window
+                .set_size(Size::Logical(LogicalSize::new(width, height)))
+                .map_err(|e| AppError::window(format!("Failed to set window size: {e}")))?;
+        }
+    }
+    // Best effort: the window has already switched, only remembering it for next time failed.
+    let _ = app.state::<WindowStateStore>().save();
+    Ok(())
 }
 
 #[tauri::command]
-fn window_minimize(app: tauri::AppHandle) -> Result<(), String> {
+fn window_minimize(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = main_window(&app)?;
-    window.minimize().map_err(|e| format!("Failed to minimize window: {e}"))
+    window.minimize().map_err(|e| AppError::window(format!("Failed to minimize window: {e}")))
 }
 
 #[tauri::command]
-fn window_toggle_maximize(app: tauri::AppHandle) -> Result<(), String> {
+fn window_toggle_maximize(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = main_window(&app)?;
     let is_maximized = window
         .is_maximized()
-        .map_err(|e| format!("Failed to read window state: {e}"))?;
+        .map_err(|e| AppError::window(format!("Failed to read window state: {e}")))?;
     if is_maximized {
-        window.unmaximize().map_err(|e| format!("Failed to restore window: {e}"))
+        window.unmaximize().map_err(|e| AppError::window(format!("Failed to restore window: {e}")))
     } else {
-        window.maximize().map_err(|e| format!("Failed to maximize window: {e}"))
+        window.maximize().map_err(|e| AppError::window(format!("Failed to maximize window: {e}")))
     }
 }
 
 #[tauri::command]
-fn window_close(app: tauri::AppHandle) -> Result<(), String> {
+fn window_close(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = main_window(&app)?;
-    window.hide().map_err(|e| format!("Failed to hide window: {e}"))
+    window.hide().map_err(|e| AppError::window(format!("Failed to hide window: {e}")))
 }
 
 #[tauri::command]
-fn window_start_dragging(app: tauri::AppHandle) -> Result<(), String> {
+fn window_start_dragging(app: tauri::AppHandle) -> Result<(), AppError> {
     let window = main_window(&app)?;
     window
         .start_dragging()
-        .map_err(|e| format!("Failed to start dragging window: {e}"))
+        .map_err(|e| AppError::window(format!("Failed to start dragging window: {e}")))
 }
 
 #[tauri::command]
-fn window_is_always_on_top(app: tauri::AppHandle) -> Result<bool, String> {
+fn window_is_always_on_top(app: tauri::AppHandle) -> Result<bool, AppError> {
     let window = main_window(&app)?;
     window
         .is_always_on_top()
-        .map_err(|e| format!("Failed to get always-on-top state: {e}"))
+        .map_err(|e| AppError::window(format!("Failed to get always-on-top state: {e}")))
 }
 
+/// Flips the always-on-top setting and returns whether the window is now kept on top, which a
+/// mode like post-it keeps on whatever the setting says.
 #[tauri::command]
 fn window_toggle_always_on_top(
     app: tauri::AppHandle,
     state: tauri::State<DesktopWindowState>,
-) -> Result<bool, String> {
+    settings: tauri::State<SettingsStore>,
+) -> Result<bool, AppError> {
+    settings.ensure_writable()?;
+    // Held so a mode switch cannot slip in between applying and saving the setting.
+    let mode = state
+        .mode
+        .lock()
+        .map_err(|_| AppError::window("Failed to read window mode state."))?;
     let window = main_window(&app)?;
-    let current = window
-        .is_always_on_top()
-        .map_err(|e| format!("Failed to get always-on-top state: {e}"))?;
-    let next = !current;
-    window
-        .set_always_on_top(next)
-        .map_err(|e| format!("Failed to set always-on-top state: {e}"))?;
+    let previous = settings.get()?.always_on_top;
+    let next = !previous;
+    apply_always_on_top(&window, *mode, next)?;
+    let saved = match save_settings(&app, &settings, |settings| settings.always_on_top = next) {
+        Ok(saved) => saved,
+        Err(e) => {
+            let _ = apply_always_on_top(&window, *mode, previous);
+            return Err(e);
+        }
+    };
 
     #[cfg(target_os = "windows")]
+    apply_window_opacity(&window, saved.window_opacity)?;
+
+    #[cfg(not(target_os = "windows"))]
     {
-        let opacity = *state
-            .window_opacity
-            .lock()
-            .map_err(|_| "Failed to read window opacity state.".to_string())?;
-        apply_window_opacity(&window, opacity)?;
+        let _ = saved;
     }
 
-    Ok(next)
+    Ok(mode.effective_always_on_top(next))
 }
 
 #[tauri::command]
-fn window_get_opacity(state: tauri::State<DesktopWindowState>) -> Result<f64, String> {
-    let opacity = state
-        .window_opacity
-        .lock()
-        .map_err(|_| "Failed to read window opacity state.".to_string())?;
-    Ok(*opacity)
+fn window_get_opacity(settings: tauri::State<SettingsStore>) -> Result<f64, AppError> {
+    Ok(settings.get()?.window_opacity)
 }
 
 #[tauri::command]
 fn window_set_opacity(
     app: tauri::AppHandle,
-    state: tauri::State<DesktopWindowState>,
+    settings: tauri::State<SettingsStore>,
     opacity: f64,
-) -> Result<f64, String> {
+) -> Result<f64, AppError> {
+    settings.ensure_writable()?;
     let window = main_window(&app)?;
-    let next = opacity.clamp(0.5, 1.0);
+    let next = opacity.clamp(MIN_WINDOW_OPACITY, MAX_WINDOW_OPACITY);
 
     #[cfg(target_os = "windows")]
     {
@@ -455,246 +1519,90 @@ fn window_set_opacity(
         let _ = window;
     }
 
-    let mut stored = state
-        .window_opacity
-        .lock()
-        .map_err(|_| "Failed to update window opacity state.".to_string())?;
-    *stored = next;
+    save_settings(&app, &settings, |settings| settings.window_opacity = next)?;
     Ok(next)
 }
 
 #[tauri::command]
-fn window_is_post_it_mode(state: tauri::State<DesktopWindowState>) -> Result<bool, String> {
+fn get_window_mode(state: tauri::State<DesktopWindowState>) -> Result<WindowMode, AppError> {
     let mode = state
-        .post_it_mode
+        .mode
         .lock()
-        .map_err(|_| "Failed to read post-it mode state.".to_string())?;
+        .map_err(|_| AppError::window("Failed to read window mode state."))?;
     Ok(*mode)
 }
 
+/// `mode` is taken as a string so an unknown mode is reported as invalid input rather than as a
+/// failed deserialization.
 #[tauri::command]
-fn window_toggle_post_it_mode(
-    app: tauri::AppHandle,
-    state: tauri::State<DesktopWindowState>,
-) -> Result<bool, String> {
-    let window = main_window(&app)?;
-    let current_opacity = *state
-        .window_opacity
-        .lock()
-        .map_err(|_| "Failed to read window opacity state.".to_string())?;
-    let mut post_it_mode = state
-        .post_it_mode
-        .lock()
-        .map_err(|_| "Failed to update post-it mode state.".to_string())?;
-    let mut calendar_mode = state
-        .calendar_mode
-        .lock()
-        .map_err(|_| "Failed to update calendar mode state.".to_string())?;
-
-    if !*post_it_mode {
-        let is_maximized = window
-            .is_maximized()
-            .map_err(|e| format!("Failed to read window state: {e}"))?;
-        let inner_size = window
-            .inner_size()
-            .map_err(|e| format!("Failed to read window size: {e}"))?;
-
-        {
-            let mut normal_state = state
-                .normal_window_state
-                .lock()
-                .map_err(|_| "Failed to store normal window state.".to_string())?;
-            *normal_state = Some(NormalWindowState {
-                width: inner_size.width as f64,
-                height: inner_size.height as f64,
-                was_maximized: is_maximized,
-            });
-        }
-
-        if is_maximized {
-            window
-                .unmaximize()
-                .map_err(|e| format!("Failed to restore window from maximized state: {e}"))?;
-        }
-
-        window
-            .set_size(Size::Logical(LogicalSize::new(
-                POST_IT_WINDOW_WIDTH,
-                POST_IT_WINDOW_HEIGHT,
-            )))
-            .map_err(|e| format!("Failed to enter post-it mode size: {e}"))?;
-
-        window
-            .set_always_on_top(true)
-            .map_err(|e| format!("Failed to enable always-on-top for post-it mode: {e}"))?;
-
-        #[cfg(target_os = "windows")]
-        apply_window_opacity(&window, current_opacity)?;
-
-        *calendar_mode = false;
-        *post_it_mode = true;
-        return Ok(true);
-    }
-
-    let saved = {
-        let mut normal_state = state
-            .normal_window_state
-            .lock()
-            .map_err(|_| "Failed to load normal window state.".to_string())?;
-        normal_state.take()
-    };
+fn set_window_mode(app: tauri::AppHandle, mode: String) -> Result<WindowMode, AppError> {
+    let mode = WindowMode::from_key(&mode)
+        .ok_or_else(|| AppError::invalid_input(format!("\"{mode}\" is not a window mode.")))?;
+    change_window_mode(&app, mode)
+}
 
-    if let Some(previous) = saved {
-        window
-            .set_size(Size::Logical(LogicalSize::new(previous.width, previous.height)))
-            .map_err(|e| format!("Failed to restore window size: {e}"))?;
-        if previous.was_maximized {
-            window
-                .maximize()
-                .map_err(|e| format!("Failed to re-maximize window: {e}"))?;
-        }
-    } else {
-        window
-            .set_size(Size::Logical(LogicalSize::new(
-                DEFAULT_WINDOW_WIDTH,
-                DEFAULT_WINDOW_HEIGHT,
-            )))
-            .map_err(|e| format!("Failed to restore default window size: {e}"))?;
-    }
+/// Enters `mode`, or goes back to the normal window when already in it. Returns whether the
+/// window is in `mode` afterwards.
+fn toggle_window_mode(app: &tauri::AppHandle, mode: WindowMode) -> Result<bool, AppError> {
+    let current = get_window_mode(app.state())?;
+    let next = if current == mode { WindowMode::Normal } else { mode };
+    Ok(change_window_mode(app, next)? == mode)
+}
 
-    #[cfg(target_os = "windows")]
-    apply_window_opacity(&window, current_opacity)?;
+// The post-it and calendar commands below predate `set_window_mode` and are kept for callers
+// that still use them.
 
-    *post_it_mode = false;
-    Ok(false)
+#[tauri::command]
+fn window_is_post_it_mode(state: tauri::State<DesktopWindowState>) -> Result<bool, AppError> {
+    Ok(get_window_mode(state)? == WindowMode::PostIt)
 }
 
 #[tauri::command]
-fn window_is_calendar_mode(state: tauri::State<DesktopWindowState>) -> Result<bool, String> {
-    let mode = state
-        .calendar_mode
-        .lock()
-        .map_err(|_| "Failed to read calendar mode state.".to_string())?;
-    Ok(*mode)
+fn window_toggle_post_it_mode(app: tauri::AppHandle) -> Result<bool, AppError> {
+    toggle_window_mode(&app, WindowMode::PostIt)
 }
 
 #[tauri::command]
-fn window_toggle_calendar_mode(
-    app: tauri::AppHandle,
-    state: tauri::State<DesktopWindowState>,
-) -> Result<bool, String> {
-    let window = main_window(&app)?;
-    let current_opacity = *state
-        .window_opacity
-        .lock()
-        .map_err(|_| "Failed to read window opacity state.".to_string())?;
-
-    let mut calendar_mode = state
-        .calendar_mode
-        .lock()
-        .map_err(|_| "Failed to update calendar mode state.".to_string())?;
-    let mut post_it_mode = state
-        .post_it_mode
-        .lock()
-        .map_err(|_| "Failed to update post-it mode state.".to_string())?;
-
-    if !*calendar_mode {
-        let is_maximized = window
-            .is_maximized()
-            .map_err(|e| format!("Failed to read window state: {e}"))?;
-        let inner_size = window
-            .inner_size()
-            .map_err(|e| format!("Failed to read window size: {e}"))?;
-
-        {
-            let mut normal_state = state
-                .normal_window_state
-                .lock()
-                .map_err(|_| "Failed to store normal window state.".to_string())?;
-            if normal_state.is_none() {
-                *normal_state = Some(NormalWindowState {
-                    width: inner_size.width as f64,
-                    height: inner_size.height as f64,
-                    was_maximized: is_maximized,
-                });
-            }
-        }
-
-        if is_maximized {
-            window
-                .unmaximize()
-                .map_err(|e| format!("Failed to restore window from maximized state: {e}"))?;
-        }
-
-        window
-            .set_size(Size::Logical(LogicalSize::new(
-                CALENDAR_WINDOW_WIDTH,
-                CALENDAR_WINDOW_HEIGHT,
-            )))
-            .map_err(|e| format!("Failed to enter calendar mode size: {e}"))?;
-
-        #[cfg(target_os = "windows")]
-        apply_window_opacity(&window, current_opacity)?;
-
-        *post_it_mode = false;
-        *calendar_mode = true;
-        return Ok(true);
-    }
-
-    let saved = {
-        let mut normal_state = state
-            .normal_window_state
-            .lock()
-            .map_err(|_| "Failed to load normal window state.".to_string())?;
-        normal_state.take()
-    };
-
-    if let Some(previous) = saved {
-        window
-            .set_size(Size::Logical(LogicalSize::new(previous.width, previous.height)))
-            .map_err(|e| format!("Failed to restore window size: {e}"))?;
-        if previous.was_maximized {
-            window
-                .maximize()
-                .map_err(|e| format!("Failed to re-maximize window: {e}"))?;
-        }
-    } else {
-        window
-            .set_size(Size::Logical(LogicalSize::new(
-                DEFAULT_WINDOW_WIDTH,
-                DEFAULT_WINDOW_HEIGHT,
-            )))
-            .map_err(|e| format!("Failed to restore default window size: {e}"))?;
-    }
-
-    #[cfg(target_os = "windows")]
-    apply_window_opacity(&window, current_opacity)?;
+fn window_is_calendar_mode(state: tauri::State<DesktopWindowState>) -> Result<bool, AppError> {
+    Ok(get_window_mode(state)? == WindowMode::Calendar)
+}
 
-    *calendar_mode = false;
-    Ok(false)
+#[tauri::command]
+fn window_toggle_calendar_mode(app: tauri::AppHandle) -> Result<bool, AppError> {
+    toggle_window_mode(&app, WindowMode::Calendar)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .manage(DesktopWindowState::default())
+        .manage(CycleWatchers::default())
         .setup(|app| {
-            let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)
-                .map_err(|e| format!("Failed to create tray menu item: {e}"))?;
-            let tray_menu = Menu::with_items(app, &[&quit_item])
-                .map_err(|e| format!("Failed to create tray menu: {e}"))?;
+            let location = app_data_dir(app.handle())?;
+            let storage = FsStorage::new(location.path.clone());
+            let settings = SettingsStore::open(&location.path);
+            let geometries = WindowStateStore::open(&location.path);
+            let saved_locale = settings.get().ok().and_then(|settings| settings.locale);
+            i18n::set_current(saved_locale.unwrap_or_else(i18n::system_locale));
+            app.manage(storage);
+            app.manage(settings);
+            app.manage(geometries);
+            app.manage(location);
+
+            let tray_menu = tray_menu(app.handle()).map_err(|e| format!("Failed to create tray menu: {e}"))?;
 
             let tray_icon = app
                 .default_window_icon()
                 .ok_or_else(|| "Default window icon is missing.".to_string())?
                 .clone();
 
-            TrayIconBuilder::new()
+            TrayIconBuilder::with_id(TRAY_ID)
                 .icon(tray_icon)
                 .menu(&tray_menu)
                 .show_menu_on_left_click(false)
                 .on_menu_event(|app: &tauri::AppHandle, event: MenuEvent| {
                     if event.id().as_ref() == "quit" {
+                        save_window_geometry(app);
                         app.exit(0);
                     }
                 })
@@ -714,22 +1622,75 @@ pub fn run() {
                 .build(app)
                 .map_err(|e| format!("Failed to build tray icon: {e}"))?;
 
+            // Best effort: the window still opens, in its default mode, if this fails. It starts
+            // hidden so it does not flash at the default position first.
+            let _ = restore_window_settings(app.handle());
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+            }
+
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let WindowEvent::CloseRequested { api, .. } = event {
+        .on_window_event(|window, event| match event {
+            WindowEvent::CloseRequested { api, .. } => {
                 api.prevent_close();
+                save_window_geometry(window.app_handle());
                 let _ = window.hide();
             }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                let app = window.app_handle();
+                if let Some(mode) = current_window_mode(&app.state::<DesktopWindowState>()) {
+                    let _ = remember_window_geometry(app, mode);
+                }
+            }
+            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             pick_folder,
             load_index,
             select_cycle,
             create_cycle,
+            duplicate_cycle,
+            rollover_cycle,
             import_cycle,
+            scan_for_cycles,
+            import_cycles,
+            rename_cycle,
+            relocate_cycle,
+            delete_cycle,
+            unregister_cycle,
+            archive_cycle,
             load_cycle_data,
+            get_cycle_revision,
+            convert_cycle_storage,
+            unwatch_cycle,
             save_cycle_data,
+            list_conflict_copies,
+            merge_conflict_copies,
+            resolve_conflict_copies,
+            upsert_goal,
+            upsert_work,
+            upsert_task,
+            delete_goal,
+            delete_work,
+            delete_task,
+            toggle_task,
+            move_work_to_goal,
+            validate_cycle,
+            repair_cycle,
+            undo,
+            redo,
+            journal_status,
+            list_snapshots,
+            diff_snapshot,
+            restore_snapshot,
+            restore_snapshot_entity,
+            get_data_dir,
+            migrate_index,
+            get_locale,
+            set_locale,
+            get_settings,
+            update_settings,
             window_minimize,
             window_toggle_maximize,
             window_close,
@@ -738,6 +1699,8 @@ pub fn run() {
             window_toggle_always_on_top,
             window_get_opacity,
             window_set_opacity,
+            get_window_mode,
+            set_window_mode,
             window_is_post_it_mode,
             window_toggle_post_it_mode,
             window_is_calendar_mode,
@@ -0,0 +1,294 @@
+use crate::error::AppError;
+use crate::models::{CycleData, EntityKind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Both sides changed the same entity (or the same fields of it) in different ways.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityConflict {
+    pub kind: EntityKind,
+    pub id: String,
+    /// Conflicting fields; empty when one side deleted the entity and the other edited it.
+    pub fields: Vec<String>,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+    /// File name of the conflict copy `theirs` came from.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    /// Result of the merge; conflicting entities keep "ours" until resolved.
+    pub merged: CycleData,
+    pub conflicts: Vec<EntityConflict>,
+    /// Conflict copies that were merged.
+    pub copies: Vec<String>,
+    /// True when there were no conflicts and the merge was saved.
+    pub applied: bool,
+}
+
+/// Sibling files a sync client created next to `cycle_data.json` when two machines edited it at
+/// once, e.g. `cycle_data (Jane's conflicted copy 2024-05-01).json`,
+/// `cycle_data-DESKTOP-1A2B.json` or `cycle_data.sync-conflict-20240501-101500-ABC.json`.
+pub fn find_conflict_copies(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return vec![];
+    };
+    let mut copies = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            name != "cycle_data.json" && name.starts_with("cycle_data") && name.ends_with(".json")
+        })
+        .collect::<Vec<_>>();
+    copies.sort();
+    copies
+}
+
+fn to_entries<T: Serialize>(items: &[T]) -> Result<Vec<(String, Value)>, AppError> {
+    items
+        .iter()
+        .map(|item| {
+            let value = serde_json::to_value(item)
+                .map_err(|e| AppError::internal(format!("serialize merge entity error: {e}")))?;
+            let id = value.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+            Ok((id, value))
+        })
+        .collect()
+}
+
+fn lookup<'a>(entries: &'a [(String, Value)], id: &str) -> Option<&'a Value> {
+    entries.iter().find(|(entry_id, _)| entry_id == id).map(|(_, value)| value)
+}
+
+/// Three-way merge of two objects field by field. Returns the merged object and the fields both
+/// sides changed differently (those keep `ours`).
+fn merge_fields(base: &Map<String, Value>, ours: &Map<String, Value>, theirs: &Map<String, Value>) -> (Map<String, Value>, Vec<String>) {
+    let keys = ours.keys().chain(theirs.keys()).chain(base.keys()).cloned().collect::<BTreeSet<_>>();
+    let mut merged = ours.clone();
+    let mut conflicts = vec![];
+    for key in keys {
+        let (b, o, t) = (base.get(&key), ours.get(&key), theirs.get(&key));
+        if o == t || t == b {
+            continue;
+        }
+        if o == b {
+            match t {
+                Some(value) => merged.insert(key, value.clone()),
+                None => merged.remove(&key),
+            };
+        } else {
+            conflicts.push(key);
+        }
+    }
+    (merged, conflicts)
+}
+
+fn merge_list<T: Serialize + DeserializeOwned>(
+    kind: EntityKind,
+    base: &[T],
+    ours: &[T],
+    theirs: &[T],
+    source: &str,
+    conflicts: &mut Vec<EntityConflict>,
+) -> Result<Vec<T>, AppError> {
+    let (base, ours, theirs) = (to_entries(base)?, to_entries(ours)?, to_entries(theirs)?);
+    let mut order = ours.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+    for (id, _) in &theirs {
+        if !order.contains(id) {
+            order.push(id.clone());
+        }
+    }
+
+    let mut merged = vec![];
+    for id in order {
+        let (b, o, t) = (lookup(&base, &id), lookup(&ours, &id), lookup(&theirs, &id));
+        let resolved = if o == t || t == b {
+            o.cloned()
+        } else if o == b {
+            t.cloned()
+        } else if let (Some(Value::Object(om)), Some(Value::Object(tm))) = (o, t) {
+            let empty = Map::new();
+            let bm = b.and_then(Value::as_object).unwrap_or(&empty);
+            let (fields_merged, fields) = merge_fields(bm, om, tm);
+            if !fields.is_empty() {
+                conflicts.push(EntityConflict {
+                    kind,
+                    id: id.clone(),
+                    fields,
+                    base: b.cloned(),
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                    source: source.to_string(),
+                });
+            }
+            Some(Value::Object(fields_merged))
+        } else {
+            conflicts.push(EntityConflict {
+                kind,
+                id: id.clone(),
+                fields: vec![],
+                base: b.cloned(),
+                ours: o.cloned(),
+                theirs: t.cloned(),
+                source: source.to_string(),
+            });
+            o.or(t).cloned()
+        };
+
+        if let Some(value) = resolved {
+            let item = serde_json::from_value(value)
+                .map_err(|e| AppError::corrupt(format!("parse merge entity error: {e}")))?;
+            merged.push(item);
+        }
+    }
+    Ok(merged)
+}
+
+/// Per-entity three-way merge of `theirs` into `ours`, keyed on goal/work/task ids.
+pub fn merge_cycle_data(
+    base: &CycleData,
+    ours: &CycleData,
+    theirs: &CycleData,
+    source: &str,
+    conflicts: &mut Vec<EntityConflict>,
+) -> Result<CycleData, AppError> {
+    let mut merged = ours.clone();
+    merged.goals = merge_list(EntityKind::Goal, &base.goals, &ours.goals, &theirs.goals, source, conflicts)?;
+    merged.works = merge_list(EntityKind::Work, &base.works, &ours.works, &theirs.works, source, conflicts)?;
+    merged.tasks = merge_list(EntityKind::Task, &base.tasks, &ours.tasks, &theirs.tasks, source, conflicts)?;
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cycle(goals: Value, tasks: Value) -> CycleData {
+        serde_json::from_value(json!({
+            "schemaVersion": 2,
+            "id": "c1",
+            "name": "Cycle",
+            "createdAt": "2026-01-01T00:00:00Z",
+            "goals": goals,
+            "tasks": tasks,
+        }))
+        .unwrap()
+    }
+
+    fn goal(id: &str, title: &str, end_date: &str) -> Value {
+        json!({ "id": id, "cycleId": "c1", "title": title, "endDate": end_date })
+    }
+
+    fn task(id: &str, title: &str) -> Value {
+        json!({ "id": id, "cycleId": "c1", "workId": "w1", "title": title })
+    }
+
+    fn merge(base: &CycleData, ours: &CycleData, theirs: &CycleData) -> (CycleData, Vec<EntityConflict>) {
+        let mut conflicts = vec![];
+        let merged = merge_cycle_data(base, ours, theirs, "copy.json", &mut conflicts).unwrap();
+        (merged, conflicts)
+    }
+
+    #[test]
+    fn changes_to_different_fields_and_entities_both_apply() {
+        let base = cycle(json!([goal("g1", "Ship", "2026-02-01")]), json!([task("t1", "Write"), task("t2", "Test")]));
+        let ours = cycle(json!([goal("g1", "Ship it", "2026-02-01")]), json!([task("t1", "Write"), task("t2", "Test")]));
+        let theirs = cycle(
+            json!([goal("g1", "Ship", "2026-03-01")]),
+            json!([task("t1", "Write"), task("t3", "Release")]),
+        );
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.goals[0].title, "Ship it");
+        assert_eq!(merged.goals[0].end_date.as_deref(), Some("2026-03-01"));
+        let ids = merged.tasks.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["t1", "t3"]);
+    }
+
+    #[test]
+    fn the_same_field_changed_twice_is_a_conflict_that_keeps_ours() {
+        let base = cycle(json!([goal("g1", "Ship", "2026-02-01")]), json!([]));
+        let ours = cycle(json!([goal("g1", "Ship it", "2026-02-01")]), json!([]));
+        let theirs = cycle(json!([goal("g1", "Ship now", "2026-03-01")]), json!([]));
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].id, "g1");
+        assert_eq!(conflicts[0].fields, ["title"]);
+        assert_eq!(conflicts[0].source, "copy.json");
+        assert_eq!(merged.goals[0].title, "Ship it");
+        assert_eq!(merged.goals[0].end_date.as_deref(), Some("2026-03-01"));
+    }
+
+    #[test]
+    fn an_edit_against_a_delete_is_a_conflict_that_keeps_the_entity() {
+        let base = cycle(json!([]), json!([task("t1", "Write")]));
+        let ours = cycle(json!([]), json!([task("t1", "Write docs")]));
+        let theirs = cycle(json!([]), json!([]));
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].fields.is_empty());
+        assert!(conflicts[0].theirs.is_none());
+        assert_eq!(merged.tasks[0].title, "Write docs");
+    }
+
+    #[test]
+    fn a_delete_of_an_unchanged_entity_applies() {
+        let base = cycle(json!([]), json!([task("t1", "Write"), task("t2", "Test")]));
+        let ours = base.clone();
+        let theirs = cycle(json!([]), json!([task("t2", "Test")]));
+
+        let (merged, conflicts) = merge(&base, &ours, &theirs);
+
+        assert!(conflicts.is_empty());
+        assert_eq!(merged.tasks.len(), 1);
+        assert_eq!(merged.tasks[0].id, "t2");
+    }
+
+    #[test]
+    fn finds_sync_conflict_copies_next_to_the_cycle_file() {
+        let folder = std::env::temp_dir().join(format!("cycle-merge-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(folder.join("cycle_data.dir.json")).unwrap();
+        for name in [
+            "cycle_data.json",
+            "cycle_data (Jane's conflicted copy 2024-05-01).json",
+            "cycle_data-DESKTOP-1A2B.json",
+            "cycle_data.sync-conflict-20240501-101500-ABC.json",
+            "cycle_data.json.bak",
+            "notes.json",
+        ] {
+            fs::write(folder.join(name), "{}").unwrap();
+        }
+
+        let names = find_conflict_copies(&folder)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let _ = fs::remove_dir_all(&folder);
+
+        assert_eq!(
+            names,
+            [
+                "cycle_data (Jane's conflicted copy 2024-05-01).json",
+                "cycle_data-DESKTOP-1A2B.json",
+                "cycle_data.sync-conflict-20240501-101500-ABC.json",
+            ]
+        );
+    }
+}
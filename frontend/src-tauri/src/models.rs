@@ -0,0 +1,255 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::schema::{CYCLE_SCHEMA_VERSION, INDEX_SCHEMA_VERSION};
+use serde_json::{Map, Value};
+
+/// Status shared by Works and (derived) Goals, mirroring `WorkStatus` in `types/models.ts`.
+///
+/// Values the backend does not know are kept verbatim in `Other` so a file written by a newer
+/// frontend still loads and round-trips unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum WorkStatus {
+    #[default]
+    NotStarted,
+    InProgress,
+    Done,
+    Other(String),
+}
+
+impl WorkStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WorkStatus::NotStarted => "NOT_STARTED",
+            WorkStatus::InProgress => "IN_PROGRESS",
+            WorkStatus::Done => "DONE",
+            WorkStatus::Other(raw) => raw,
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, WorkStatus::Other(_))
+    }
+}
+
+impl From<&str> for WorkStatus {
+    fn from(raw: &str) -> Self {
+        match raw {
+            "NOT_STARTED" => WorkStatus::NotStarted,
+            "IN_PROGRESS" => WorkStatus::InProgress,
+            "DONE" => WorkStatus::Done,
+            other => WorkStatus::Other(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for WorkStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for WorkStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Ok(WorkStatus::from(raw.as_str()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Goal,
+    Work,
+    Task,
+}
+
+/// How a cycle folder stores its data: `cycle_data.json` or a `cycle_data.sqlite` database.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl StorageKind {
+    pub fn is_json(&self) -> bool {
+        *self == StorageKind::Json
+    }
+}
+
+/// Where a rolled-over entity was copied from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityOrigin {
+    pub cycle_id: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    pub id: String,
+    #[serde(default)]
+    pub cycle_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_over_from: Option<EntityOrigin>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Work {
+    pub id: String,
+    #[serde(default)]
+    pub cycle_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal_id: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub status: WorkStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_over_from: Option<EntityOrigin>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: String,
+    #[serde(default)]
+    pub cycle_id: String,
+    #[serde(default)]
+    pub work_id: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_over_from: Option<EntityOrigin>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleMeta {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub folder_path: String,
+    /// Archived cycles stay in the index (and in search) but are hidden from the cycle selector.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// Set while loading the index when `folder_path` no longer exists on disk.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+    /// Set when the cycle was closed by a rollover into a newer cycle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "StorageKind::is_json")]
+    pub storage: StorageKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexData {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub cycles: Vec<CycleMeta>,
+    pub selected_cycle_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleData {
+    #[serde(default)]
+    pub schema_version: u32,
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+    #[serde(default)]
+    pub works: Vec<Work>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for IndexData {
+    fn default() -> Self {
+        Self {
+            schema_version: INDEX_SCHEMA_VERSION,
+            cycles: vec![],
+            selected_cycle_id: None,
+        }
+    }
+}
+
+impl CycleData {
+    pub fn empty(meta: &CycleMeta) -> Self {
+        Self {
+            schema_version: CYCLE_SCHEMA_VERSION,
+            id: meta.id.clone(),
+            name: meta.name.clone(),
+            created_at: meta.created_at.clone(),
+            goals: vec![],
+            works: vec![],
+            tasks: vec![],
+            extra: Map::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unknown_statuses_and_fields_round_trip_unchanged() {
+        let raw = json!({
+            "schemaVersion": CYCLE_SCHEMA_VERSION,
+            "id": "c1",
+            "name": "Q4",
+            "createdAt": "2026-10-01T09:00:00.000+09:00",
+            "theme": { "color": "teal" },
+            "goals": [{ "id": "g1", "cycleId": "c1", "title": "Ship", "priority": 2 }],
+            "works": [
+                { "id": "w1", "cycleId": "c1", "goalId": "g1", "title": "Plan", "status": "BLOCKED", "estimate": 3 },
+                { "id": "w2", "cycleId": "c1", "title": "Build", "status": "DONE" }
+            ],
+            "tasks": [{ "id": "t1", "cycleId": "c1", "workId": "w1", "title": "Draft", "done": false, "tags": ["a"] }]
+        });
+        let data: CycleData = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(data.works[0].status, WorkStatus::Other("BLOCKED".to_string()));
+        assert!(!data.works[0].status.is_known());
+        assert_eq!(data.works[1].status, WorkStatus::Done);
+        assert_eq!(serde_json::to_value(&data).unwrap(), raw);
+    }
+
+    #[test]
+    fn a_missing_status_reads_as_not_started() {
+        let work: Work = serde_json::from_value(json!({ "id": "w1" })).unwrap();
+        assert_eq!(work.status, WorkStatus::NotStarted);
+        assert_eq!(serde_json::to_value(&work).unwrap()["status"], "NOT_STARTED");
+    }
+}
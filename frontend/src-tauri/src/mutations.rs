@@ -0,0 +1,253 @@
+use crate::error::AppError;
+use crate::models::{CycleData, Goal, Task, Work};
+use crate::uid;
+
+fn require_title(title: &str) -> Result<String, AppError> {
+    let trimmed = title.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::invalid_input("Title cannot be empty."));
+    }
+    Ok(trimmed.to_string())
+}
+
+fn require_goal(data: &CycleData, goal_id: &str) -> Result<(), AppError> {
+    if data.goals.iter().any(|g| g.id == goal_id) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("Goal {goal_id} does not exist in this Cycle.")))
+    }
+}
+
+fn require_work(data: &CycleData, work_id: &str) -> Result<(), AppError> {
+    if data.works.iter().any(|w| w.id == work_id) {
+        Ok(())
+    } else {
+        Err(AppError::not_found(format!("Work {work_id} does not exist in this Cycle.")))
+    }
+}
+
+/// Replaces the entity with the same id, or inserts it (at the front when `prepend`, the way the
+/// UI lists new Works first).
+fn upsert<T: Clone>(items: &mut Vec<T>, item: T, id: impl Fn(&T) -> &str, prepend: bool) -> T {
+    match items.iter().position(|existing| id(existing) == id(&item)) {
+        Some(position) => items[position] = item.clone(),
+        None if prepend => items.insert(0, item.clone()),
+        None => items.push(item.clone()),
+    }
+    item
+}
+
+pub fn upsert_goal(data: &mut CycleData, goal: Goal) -> Result<Goal, AppError> {
+    let mut goal = goal;
+    goal.title = require_title(&goal.title)?;
+    if goal.id.is_empty() {
+        goal.id = uid("goal");
+    }
+    goal.cycle_id = data.id.clone();
+    Ok(upsert(&mut data.goals, goal, |g| &g.id, false))
+}
+
+pub fn upsert_work(data: &mut CycleData, work: Work) -> Result<Work, AppError> {
+    let mut work = work;
+    work.title = require_title(&work.title)?;
+    if !work.status.is_known() {
+        return Err(AppError::invalid_input(format!("\"{}\" is not a valid Work status.", work.status.as_str())));
+    }
+    if let Some(goal_id) = &work.goal_id {
+        require_goal(data, goal_id)?;
+    }
+    if work.id.is_empty() {
+        work.id = uid("work");
+    }
+    work.cycle_id = data.id.clone();
+    Ok(upsert(&mut data.works, work, |w| &w.id, true))
+}
+
+pub fn upsert_task(data: &mut CycleData, task: Task) -> Result<Task, AppError> {
+    let mut task = task;
+    task.title = require_title(&task.title)?;
+    require_work(data, &task.work_id)?;
+    if task.id.is_empty() {
+        task.id = uid("task");
+    }
+    task.cycle_id = data.id.clone();
+    Ok(upsert(&mut data.tasks, task, |t| &t.id, false))
+}
+
+/// Deletes the Goal together with its Works and their Tasks, like the Goal List tab does.
+pub fn delete_goal(data: &mut CycleData, goal_id: &str) -> Result<Goal, AppError> {
+    let position = data
+        .goals
+        .iter()
+        .position(|g| g.id == goal_id)
+        .ok_or_else(|| AppError::not_found(format!("Goal {goal_id} does not exist in this Cycle.")))?;
+    let removed = data.goals.remove(position);
+    let work_ids = data
+        .works
+        .iter()
+        .filter(|w| w.goal_id.as_deref() == Some(goal_id))
+        .map(|w| w.id.clone())
+        .collect::<Vec<_>>();
+    data.works.retain(|w| !work_ids.contains(&w.id));
+    data.tasks.retain(|t| !work_ids.contains(&t.work_id));
+    Ok(removed)
+}
+
+/// Deletes the Work together with its Tasks.
+pub fn delete_work(data: &mut CycleData, work_id: &str) -> Result<Work, AppError> {
+    let position = data
+        .works
+        .iter()
+        .position(|w| w.id == work_id)
+        .ok_or_else(|| AppError::not_found(format!("Work {work_id} does not exist in this Cycle.")))?;
+    let removed = data.works.remove(position);
+    data.tasks.retain(|t| t.work_id != work_id);
+    Ok(removed)
+}
+
+pub fn delete_task(data: &mut CycleData, task_id: &str) -> Result<Task, AppError> {
+    let position = data
+        .tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| AppError::not_found(format!("Task {task_id} does not exist in this Cycle.")))?;
+    Ok(data.tasks.remove(position))
+}
+
+pub fn toggle_task(data: &mut CycleData, task_id: &str) -> Result<Task, AppError> {
+    let task = data
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| AppError::not_found(format!("Task {task_id} does not exist in this Cycle.")))?;
+    task.done = !task.done;
+    Ok(task.clone())
+}
+
+/// Moves a Work under another Goal, or detaches it when `goal_id` is `None`.
+pub fn move_work_to_goal(data: &mut CycleData, work_id: &str, goal_id: Option<String>) -> Result<Work, AppError> {
+    if let Some(goal_id) = &goal_id {
+        require_goal(data, goal_id)?;
+    }
+    let work = data
+        .works
+        .iter_mut()
+        .find(|w| w.id == work_id)
+        .ok_or_else(|| AppError::not_found(format!("Work {work_id} does not exist in this Cycle.")))?;
+    work.goal_id = goal_id;
+    Ok(work.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use crate::models::WorkStatus;
+    use serde_json::{json, Value};
+
+    fn cycle() -> CycleData {
+        serde_json::from_value(json!({
+            "schemaVersion": 1,
+            "id": "cycle_a",
+            "name": "A",
+            "createdAt": "2026-10-01T09:00:00.000+09:00",
+            "goals": [
+                { "id": "g1", "cycleId": "cycle_a", "title": "Ship" },
+                { "id": "g2", "cycleId": "cycle_a", "title": "Learn" }
+            ],
+            "works": [
+                { "id": "w1", "cycleId": "cycle_a", "goalId": "g1", "title": "Plan", "status": "DONE" },
+                { "id": "w2", "cycleId": "cycle_a", "goalId": "g1", "title": "Build", "status": "IN_PROGRESS" },
+                { "id": "w3", "cycleId": "cycle_a", "goalId": "g2", "title": "Read", "status": "NOT_STARTED" }
+            ],
+            "tasks": [
+                { "id": "t1", "workId": "w1", "title": "Draft", "done": true },
+                { "id": "t2", "workId": "w2", "title": "Code", "done": false },
+                { "id": "t3", "workId": "w3", "title": "Chapter 1", "done": false }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn entity<T: serde::de::DeserializeOwned>(value: Value) -> T {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn ids<T>(items: &[T], id: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(id).collect()
+    }
+
+    #[test]
+    fn upserts_insert_new_entities_and_replace_existing_ones() {
+        let mut data = cycle();
+        let work = upsert_work(&mut data, entity(json!({ "id": "", "title": " Test ", "goalId": "g2" }))).unwrap();
+        assert!(work.id.starts_with("work_"));
+        assert_eq!((work.title.as_str(), work.cycle_id.as_str()), ("Test", "cycle_a"));
+        assert_eq!(data.works[0].id, work.id);
+
+        upsert_goal(&mut data, entity(json!({ "id": "g1", "title": "Ship it" }))).unwrap();
+        assert_eq!(ids(&data.goals, |g| &g.title), ["Ship it", "Learn"]);
+        let task = upsert_task(&mut data, entity(json!({ "id": "", "workId": work.id, "title": "Write" }))).unwrap();
+        assert_eq!(data.tasks.last().unwrap().id, task.id);
+    }
+
+    #[test]
+    fn rejects_parents_that_do_not_exist() {
+        let mut data = cycle();
+        let work = entity(json!({ "id": "", "title": "Orphan", "goalId": "g_gone" }));
+        assert_eq!(upsert_work(&mut data, work).unwrap_err().code, ErrorCode::NotFound);
+        let task = entity(json!({ "id": "", "title": "Orphan", "workId": "w_gone" }));
+        assert_eq!(upsert_task(&mut data, task).unwrap_err().code, ErrorCode::NotFound);
+        let error = upsert_task(&mut data, entity(json!({ "id": "", "title": "No work" }))).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!((data.works.len(), data.tasks.len()), (3, 3));
+    }
+
+    #[test]
+    fn rejects_unknown_statuses_and_empty_titles() {
+        let mut data = cycle();
+        let error = upsert_work(&mut data, entity(json!({ "id": "w2", "title": "Build", "status": "BLOCKED" })));
+        assert_eq!(error.unwrap_err().code, ErrorCode::InvalidInput);
+        assert_eq!(data.works[1].status, WorkStatus::InProgress);
+        let error = upsert_goal(&mut data, entity(json!({ "id": "g1", "title": "   " }))).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidInput);
+        assert_eq!(data.goals[0].title, "Ship");
+    }
+
+    #[test]
+    fn deleting_a_goal_deletes_its_works_and_their_tasks() {
+        let mut data = cycle();
+        assert_eq!(delete_goal(&mut data, "g1").unwrap().id, "g1");
+        assert_eq!(ids(&data.goals, |g| &g.id), ["g2"]);
+        assert_eq!(ids(&data.works, |w| &w.id), ["w3"]);
+        assert_eq!(ids(&data.tasks, |t| &t.id), ["t3"]);
+
+        assert_eq!(delete_work(&mut data, "w3").unwrap().id, "w3");
+        assert!(data.works.is_empty() && data.tasks.is_empty());
+        assert_eq!(delete_goal(&mut data, "g1").unwrap_err().code, ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn toggling_a_task_flips_it_and_a_missing_one_is_not_found() {
+        let mut data = cycle();
+        assert!(toggle_task(&mut data, "t2").unwrap().done);
+        assert!(!toggle_task(&mut data, "t2").unwrap().done);
+        assert_eq!(toggle_task(&mut data, "t_gone").unwrap_err().code, ErrorCode::NotFound);
+        assert_eq!(delete_task(&mut data, "t_gone").unwrap_err().code, ErrorCode::NotFound);
+    }
+
+    #[test]
+    fn moves_a_work_between_goals_and_detaches_it() {
+        let mut data = cycle();
+        let moved = move_work_to_goal(&mut data, "w2", Some("g2".to_string())).unwrap();
+        assert_eq!(moved.goal_id.as_deref(), Some("g2"));
+        assert_eq!(data.works[1].goal_id.as_deref(), Some("g2"));
+        assert_eq!(move_work_to_goal(&mut data, "w2", None).unwrap().goal_id, None);
+
+        let error = move_work_to_goal(&mut data, "w2", Some("g_gone".to_string())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!(data.works[1].goal_id, None);
+        let error = move_work_to_goal(&mut data, "w_gone", Some("g1".to_string())).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NotFound);
+    }
+}
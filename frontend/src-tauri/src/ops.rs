@@ -0,0 +1,721 @@
+use crate::error::{AppError, ErrorCode};
+use crate::models::{CycleData, CycleMeta, IndexData, StorageKind};
+use crate::storage::Storage;
+use crate::watch::CycleWatchers;
+use crate::dates::now_iso;
+use crate::rollover::{plan_rollover, RolloverOptions};
+use crate::{
+    commit_cycle_data, create_cycle_folder, cycle_data_not_found, cycle_folder_name, ensure_cycle_data, find_cycle,
+    is_named_after, normalize_display_path, normalize_incoming, read_folder_data, read_import_data, read_index,
+    record_saved_cycle, register_imported_cycle, register_new_cycle, remove_cycle_entry, reselect_away_from,
+    unique_child_path, update_sync_base, write_cycle_data, write_index, write_watched_cycle_data,
+};
+use std::path::Path;
+
+pub fn load_index(storage: &dyn Storage) -> Result<IndexData, AppError> {
+    read_index(storage)
+}
+
+pub fn select_cycle(storage: &dyn Storage, cycle_id: &str) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    if find_cycle(&index, cycle_id).is_none() {
+        return Err(AppError::new(ErrorCode::CycleNotFound, "Selected Cycle does not exist.").with_cycle(cycle_id));
+    }
+    index.selected_cycle_id = Some(cycle_id.to_string());
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+pub fn create_cycle(storage: &dyn Storage, name: String, parent_dir: &str) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    let meta = create_cycle_folder(storage, parent_dir, name)?;
+    let data = CycleData::empty(&meta);
+
+    write_cycle_data(storage, &meta, &data)?;
+    register_new_cycle(&mut index, meta);
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+/// Closes `source_id` and starts a new cycle with every Work that is not done, its open Tasks and
+/// its Goal. Any failure before the index is written undoes the steps already taken.
+pub fn rollover_cycle(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    source_id: &str,
+    name: String,
+    parent_dir: &str,
+    options: &RolloverOptions,
+) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    let source = find_cycle(&index, source_id).ok_or_else(|| AppError::cycle_not_found(source_id))?;
+    if source.closed_at.is_some() {
+        return Err(AppError::invalid_input("This Cycle was already closed by a rollover.").with_cycle(source_id));
+    }
+    let source_data = ensure_cycle_data(storage, &source)?;
+
+    let meta = create_cycle_folder(storage, parent_dir, name)?;
+    let remove_new_folder = || {
+        let _ = storage.remove_dir(&meta.folder_path);
+    };
+    let plan = plan_rollover(&source_data, &meta, options);
+
+    // Until the index is written only the cycle data changes; sync bases, snapshots and the undo
+    // journal follow once the rollover is committed, so a rolled back one leaves none of them behind.
+    if let Err(e) = write_watched_cycle_data(storage, watchers, &meta, &plan.next) {
+        remove_new_folder();
+        return Err(e);
+    }
+    if let Some(remaining) = &plan.remaining {
+        if let Err(e) = write_watched_cycle_data(storage, watchers, &source, remaining) {
+            remove_new_folder();
+            return Err(e);
+        }
+    }
+
+    if let Some(entry) = index.cycles.iter_mut().find(|c| c.id == source_id) {
+        entry.closed_at = Some(now_iso());
+    }
+    index.selected_cycle_id = Some(meta.id.clone());
+    index.cycles.push(meta.clone());
+    if let Err(e) = write_index(storage, &index) {
+        remove_new_folder();
+        if plan.remaining.is_some() {
+            if let Err(restore) = write_watched_cycle_data(storage, watchers, &source, &source_data) {
+                return Err(rollback_failed(e, restore, &source));
+            }
+        }
+        return Err(e);
+    }
+
+    // The rollover went through and the frontend has to pick up the new index, so bookkeeping that
+    // fails from here on is only logged.
+    let mut bookkeeping = vec![record_saved_cycle(storage, &meta, &plan.next)];
+    if let Some(remaining) = &plan.remaining {
+        bookkeeping.push(record_saved_cycle(storage, &source, remaining));
+        bookkeeping.push(storage.record_edit(&source.folder_path, &source_data, remaining));
+    }
+    for error in bookkeeping.into_iter().filter_map(Result::err) {
+        eprintln!("rollover of cycle {source_id}: {}", error.message);
+    }
+    Ok(index)
+}
+
+/// `error` extended with the failure to put `source` back, which leaves it without the Works the
+/// rollover moved out of it.
+fn rollback_failed(error: AppError, rollback: AppError, source: &CycleMeta) -> AppError {
+    let message = format!(
+        "{} Cycle \"{}\" could not be restored either and is missing the moved items: {}",
+        error.message, source.name, rollback.message
+    );
+    AppError { message, ..error }.with_cycle(&source.id)
+}
+
+pub fn import_cycle(storage: &dyn Storage, folder_path: &str) -> Result<IndexData, AppError> {
+    let (data, kind) = read_import_data(storage, folder_path)?;
+    let mut index = read_index(storage)?;
+    let selected = register_imported_cycle(&mut index, &data, kind, folder_path);
+    index.selected_cycle_id = Some(data.id.clone());
+
+    write_cycle_data(storage, &selected, &data)?;
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+/// Imports every folder in `folder_paths` or none of them: all data is read and checked before
+/// the index is touched.
+pub fn import_cycles(storage: &dyn Storage, folder_paths: &[String]) -> Result<IndexData, AppError> {
+    let mut batch: Vec<(&String, CycleData, StorageKind)> = Vec::with_capacity(folder_paths.len());
+    for folder_path in folder_paths {
+        let (data, kind) = read_import_data(storage, folder_path).map_err(|e| e.or_path(folder_path))?;
+        if batch.iter().any(|(_, other, _)| other.id == data.id) {
+            let message = "Another selected folder has the same cycle id.";
+            return Err(AppError::new(ErrorCode::InvalidFolder, message).with_path(folder_path));
+        }
+        batch.push((folder_path, data, kind));
+    }
+
+    let mut index = read_index(storage)?;
+    let mut registered = Vec::with_capacity(batch.len());
+    for (folder_path, data, kind) in &batch {
+        registered.push(register_imported_cycle(&mut index, data, *kind, folder_path));
+    }
+    if index.selected_cycle_id.is_none() {
+        index.selected_cycle_id = registered.first().map(|meta| meta.id.clone());
+    }
+
+    for (meta, (_, data, _)) in registered.iter().zip(&batch) {
+        write_cycle_data(storage, meta, data)?;
+    }
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+/// Renames a cycle and, with `rename_folder`, its folder to match the new name (`-2`, `-3`, ... when
+/// that name is taken). A failure after the folder moved puts the folder and the data back.
+pub fn rename_cycle(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    name: &str,
+    rename_folder: bool,
+) -> Result<IndexData, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("Cycle name cannot be empty."));
+    }
+
+    let mut index = read_index(storage)?;
+    let previous = find_cycle(&index, cycle_id).ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let previous_data = ensure_cycle_data(storage, &previous)?;
+
+    let mut next = previous.clone();
+    next.name = name.clone();
+
+    let old_folder = Path::new(&previous.folder_path);
+    let mut moved_folder: Option<String> = None;
+    if rename_folder {
+        let parent = old_folder
+            .parent()
+            .ok_or_else(|| AppError::invalid_input("The cycle folder has no parent folder.").with_path(old_folder))?;
+        let base = cycle_folder_name(&name, cycle_id);
+        let current = old_folder.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        if !is_named_after(current, &base) {
+            watchers.unwatch(cycle_id);
+            let target = unique_child_path(parent, &base, |path| storage.exists(&path.to_string_lossy()));
+            let target = target.to_string_lossy().to_string();
+            storage.rename_dir(&previous.folder_path, &target)?;
+            next.folder_path = normalize_display_path(&target);
+            moved_folder = Some(target);
+        }
+    }
+
+    let rollback_folder = || {
+        if let Some(target) = &moved_folder {
+            let _ = storage.rename_dir(target, &previous.folder_path);
+        }
+    };
+
+    let mut data = previous_data.clone();
+    data.name = name;
+    if let Err(e) = write_watched_cycle_data(storage, watchers, &next, &data) {
+        rollback_folder();
+        return Err(e);
+    }
+
+    if let Some(entry) = index.cycles.iter_mut().find(|c| c.id == cycle_id) {
+        *entry = next.clone();
+    }
+    if let Err(e) = write_index(storage, &index) {
+        let _ = write_watched_cycle_data(storage, watchers, &next, &previous_data);
+        rollback_folder();
+        return Err(e);
+    }
+    Ok(index)
+}
+
+/// Points a cycle at the folder it was moved to, after checking the folder holds that cycle.
+pub fn relocate_cycle(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    folder_path: &str,
+) -> Result<IndexData, AppError> {
+    let folder_path = normalize_display_path(folder_path);
+    let (data, kind) = read_folder_data(storage, &folder_path)?.ok_or_else(|| cycle_data_not_found(&folder_path))?;
+    if data.id != cycle_id {
+        let message = "The cycle data in this folder belongs to a different Cycle.";
+        return Err(AppError::new(ErrorCode::InvalidFolder, message).with_path(&folder_path).with_cycle(cycle_id));
+    }
+
+    let mut index = read_index(storage)?;
+    let cycle = index
+        .cycles
+        .iter_mut()
+        .find(|c| c.id == cycle_id)
+        .ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    cycle.folder_path = folder_path;
+    cycle.missing = false;
+    cycle.storage = kind;
+    watchers.unwatch(cycle_id);
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+/// Removes a cycle from the index and, with `trash_folder`, moves its folder to the trash. The
+/// index goes first so it never points at a trashed folder; a failed trash brings the entry back.
+pub fn delete_cycle(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    trash_folder: bool,
+) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    let previous_index = index.clone();
+    let removed = remove_cycle_entry(&mut index, cycle_id)?;
+    watchers.unwatch(cycle_id);
+    write_index(storage, &index)?;
+
+    if trash_folder && storage.exists(&removed.folder_path) {
+        if let Err(e) = storage.trash_dir(&removed.folder_path) {
+            let _ = write_index(storage, &previous_index);
+            return Err(e.with_cycle(cycle_id));
+        }
+    }
+    Ok(index)
+}
+
+/// Removes a cycle from the index and leaves its folder alone.
+pub fn unregister_cycle(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    remove_cycle_entry(&mut index, cycle_id)?;
+    watchers.unwatch(cycle_id);
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+pub fn archive_cycle(storage: &dyn Storage, cycle_id: &str, archived: bool) -> Result<IndexData, AppError> {
+    let mut index = read_index(storage)?;
+    let cycle = index
+        .cycles
+        .iter_mut()
+        .find(|c| c.id == cycle_id)
+        .ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    cycle.archived = archived;
+    if archived {
+        reselect_away_from(&mut index, cycle_id);
+    }
+    write_index(storage, &index)?;
+    Ok(index)
+}
+
+pub fn load_cycle_data(storage: &dyn Storage, cycle_id: &str) -> Result<CycleData, AppError> {
+    let index = read_index(storage)?;
+    let cycle = find_cycle(&index, cycle_id).ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let data = ensure_cycle_data(storage, &cycle)?;
+    update_sync_base(storage, &cycle, &data)?;
+    Ok(data)
+}
+
+pub fn save_cycle_data(
+    storage: &dyn Storage,
+    watchers: &CycleWatchers,
+    cycle_id: &str,
+    data: CycleData,
+    expected_revision: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let index = read_index(storage)?;
+    let cycle = find_cycle(&index, cycle_id).ok_or_else(|| AppError::cycle_not_found(cycle_id))?;
+    let next = normalize_incoming(&cycle, data);
+    commit_cycle_data(storage, watchers, &cycle, &next, expected_revision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{FsStorage, MemoryStorage};
+    use std::path::Path;
+
+    const PARENT: &str = "/cycles";
+
+    fn storage_with_parent() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        storage.add_folder(PARENT, None);
+        storage
+    }
+
+    fn cycle_data(id: &str, name: &str) -> CycleData {
+        serde_json::from_value(serde_json::json!({
+            "schemaVersion": 1,
+            "id": id,
+            "name": name,
+            "createdAt": "1700000000",
+            "works": [{ "id": "work_1", "title": "Write report", "status": "IN_PROGRESS" }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn create_cycle_registers_folder_and_selects_first_cycle() {
+        let storage = storage_with_parent();
+
+        let index = create_cycle(&storage, "Spring plan".to_string(), PARENT).unwrap();
+        assert_eq!(index.cycles.len(), 1);
+        let first = &index.cycles[0];
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(first.id.as_str()));
+        assert!(first.folder_path.starts_with(PARENT));
+        assert!(first.folder_path.contains("Spring_plan"));
+        let data = storage.cycle(&first.folder_path).unwrap();
+        assert_eq!(data.id, first.id);
+        assert!(data.goals.is_empty() && data.works.is_empty() && data.tasks.is_empty());
+
+        let index = create_cycle(&storage, "Summer plan".to_string(), PARENT).unwrap();
+        assert_eq!(index.cycles.len(), 2);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(first.id.as_str()));
+        assert_eq!(load_index(&storage).unwrap().cycles.len(), 2);
+    }
+
+    #[test]
+    fn create_cycle_names_folders_safely_for_any_language() {
+        let storage = storage_with_parent();
+        for name in ["봄 계획", "con", "con"] {
+            create_cycle(&storage, name.to_string(), PARENT).unwrap();
+        }
+
+        let cycles = load_index(&storage).unwrap().cycles;
+        assert!(cycles[0].folder_path.contains("/봄_계획_"));
+        assert!(cycles[1].folder_path.contains("/con_"));
+        assert_ne!(cycles[1].folder_path, cycles[2].folder_path);
+        assert!(cycles[0].id < cycles[1].id && cycles[1].id < cycles[2].id);
+    }
+
+    #[test]
+    fn create_cycle_rejects_missing_parent() {
+        let storage = MemoryStorage::default();
+        assert!(create_cycle(&storage, "Plan".to_string(), "/nowhere").is_err());
+        assert!(load_index(&storage).unwrap().cycles.is_empty());
+    }
+
+    #[test]
+    fn select_cycle_only_accepts_registered_cycles() {
+        let storage = storage_with_parent();
+        create_cycle(&storage, "A".to_string(), PARENT).unwrap();
+        let index = create_cycle(&storage, "B".to_string(), PARENT).unwrap();
+        let second = index.cycles[1].id.clone();
+
+        assert_eq!(select_cycle(&storage, &second).unwrap().selected_cycle_id, Some(second.clone()));
+        assert_eq!(load_index(&storage).unwrap().selected_cycle_id, Some(second.clone()));
+        assert_eq!(select_cycle(&storage, "cycle_unknown").unwrap_err().code, ErrorCode::CycleNotFound);
+        assert_eq!(load_index(&storage).unwrap().selected_cycle_id, Some(second));
+    }
+
+    #[test]
+    fn import_cycle_registers_and_selects_existing_folder() {
+        let storage = storage_with_parent();
+        create_cycle(&storage, "Local".to_string(), PARENT).unwrap();
+        storage.add_folder("/shared/team", Some(cycle_data("cycle_team", "Team")));
+
+        let index = import_cycle(&storage, "/shared/team").unwrap();
+        assert_eq!(index.cycles.len(), 2);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some("cycle_team"));
+        let imported = index.cycles.iter().find(|c| c.id == "cycle_team").unwrap();
+        assert_eq!(imported.name, "Team");
+        assert_eq!(imported.folder_path, "/shared/team");
+
+        // Importing the same folder again refreshes the entry instead of adding a second one.
+        assert_eq!(import_cycle(&storage, "/shared/team").unwrap().cycles.len(), 2);
+        let error = import_cycle(&storage, "/shared/empty").unwrap_err();
+        assert_eq!(error.code, ErrorCode::CycleDataNotFound);
+        assert_eq!(error.path.as_deref(), Some("/shared/empty"));
+    }
+
+    #[test]
+    fn import_cycles_is_all_or_nothing() {
+        let storage = MemoryStorage::default();
+        storage.add_folder("/a", Some(cycle_data("cycle_a", "A")));
+        storage.add_folder("/b", Some(cycle_data("cycle_a", "Copy of A")));
+        storage.add_folder("/c", Some(cycle_data("cycle_c", "C")));
+
+        let paths = ["/a", "/b", "/c"].map(String::from);
+        let error = import_cycles(&storage, &paths).unwrap_err();
+        assert_eq!((error.code, error.path.as_deref()), (ErrorCode::InvalidFolder, Some("/b")));
+        assert!(load_index(&storage).unwrap().cycles.is_empty());
+
+        let index = import_cycles(&storage, &["/a".to_string(), "/c".to_string()]).unwrap();
+        assert_eq!(index.cycles.len(), 2);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some("cycle_a"));
+    }
+
+    #[test]
+    fn load_index_flags_missing_folders() {
+        let storage = storage_with_parent();
+        let index = create_cycle(&storage, "Gone".to_string(), PARENT).unwrap();
+        let cycle = index.cycles[0].clone();
+        storage.remove_folder(&cycle.folder_path);
+
+        let index = load_index(&storage).unwrap();
+        assert!(index.cycles[0].missing);
+        let error = load_cycle_data(&storage, &cycle.id).unwrap_err();
+        assert_eq!(error.code, ErrorCode::FolderMissing);
+        assert_eq!(error.cycle_id, Some(cycle.id));
+    }
+
+    #[test]
+    fn save_cycle_data_pins_identity_and_journals_the_edit() {
+        let storage = storage_with_parent();
+        let watchers = CycleWatchers::default();
+        let index = create_cycle(&storage, "Plan".to_string(), PARENT).unwrap();
+        let cycle = index.cycles[0].clone();
+
+        let revision = save_cycle_data(&storage, &watchers, &cycle.id, cycle_data("cycle_other", "Renamed"), None)
+            .unwrap()
+            .expect("saved data has a revision");
+
+        let saved = load_cycle_data(&storage, &cycle.id).unwrap();
+        assert_eq!(saved.id, cycle.id);
+        assert_eq!(saved.name, "Plan");
+        assert_eq!(saved.works.len(), 1);
+        assert_eq!(*storage.edits.lock().unwrap(), 1);
+        assert_eq!(storage.sync_base(&cycle.id).unwrap().works.len(), 1);
+        assert_eq!(storage.cycle_revision(&cycle.folder_path, cycle.storage), Some(revision));
+    }
+
+    #[test]
+    fn save_cycle_data_rejects_a_stale_revision() {
+        let storage = storage_with_parent();
+        let watchers = CycleWatchers::default();
+        let index = create_cycle(&storage, "Plan".to_string(), PARENT).unwrap();
+        let cycle = index.cycles[0].clone();
+        let loaded = storage.cycle_revision(&cycle.folder_path, cycle.storage);
+
+        let first = save_cycle_data(&storage, &watchers, &cycle.id, cycle_data(&cycle.id, "Plan"), loaded.clone());
+        assert!(first.is_ok());
+        let stale = save_cycle_data(&storage, &watchers, &cycle.id, cycle_data(&cycle.id, "Plan"), loaded);
+        assert_eq!(stale.unwrap_err().code, ErrorCode::ExternalChange);
+        let unknown = save_cycle_data(&storage, &watchers, "cycle_unknown", cycle_data("x", "x"), None);
+        assert_eq!(unknown.unwrap_err().code, ErrorCode::CycleNotFound);
+    }
+
+    /// Three cycles, the first one selected.
+    fn three_cycles(storage: &MemoryStorage) -> Vec<CycleMeta> {
+        for name in ["A", "B", "C"] {
+            create_cycle(storage, name.to_string(), PARENT).unwrap();
+        }
+        load_index(storage).unwrap().cycles
+    }
+
+    #[test]
+    fn delete_cycle_trashes_the_folder_and_selects_another_cycle() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+        let watchers = CycleWatchers::default();
+
+        let index = delete_cycle(&storage, &watchers, &cycles[0].id, true).unwrap();
+        assert_eq!(index.cycles.len(), 2);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(cycles[1].id.as_str()));
+        assert_eq!(*storage.trashed.lock().unwrap(), [cycles[0].folder_path.clone()]);
+
+        delete_cycle(&storage, &watchers, &cycles[2].id, false).unwrap();
+        assert!(storage.is_dir(&cycles[2].folder_path));
+        assert_eq!(load_index(&storage).unwrap().cycles.len(), 1);
+        let error = delete_cycle(&storage, &watchers, &cycles[2].id, true).unwrap_err();
+        assert_eq!(error.code, ErrorCode::CycleNotFound);
+    }
+
+    #[test]
+    fn delete_cycle_keeps_the_folder_when_the_index_cannot_be_written() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+        *storage.fail_index_writes.lock().unwrap() = true;
+
+        assert!(delete_cycle(&storage, &CycleWatchers::default(), &cycles[0].id, true).is_err());
+        assert!(storage.trashed.lock().unwrap().is_empty());
+        assert!(storage.is_dir(&cycles[0].folder_path));
+        assert_eq!(load_index(&storage).unwrap().cycles.len(), 3);
+    }
+
+    #[test]
+    fn delete_cycle_restores_the_entry_when_the_folder_cannot_be_trashed() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+        *storage.fail_trash.lock().unwrap() = true;
+
+        let error = delete_cycle(&storage, &CycleWatchers::default(), &cycles[0].id, true).unwrap_err();
+        assert_eq!((error.code, error.cycle_id.as_deref()), (ErrorCode::Io, Some(cycles[0].id.as_str())));
+        let index = load_index(&storage).unwrap();
+        assert_eq!(index.cycles.len(), 3);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(cycles[0].id.as_str()));
+    }
+
+    #[test]
+    fn unregister_cycle_leaves_the_folder_alone() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+
+        let index = unregister_cycle(&storage, &CycleWatchers::default(), &cycles[1].id).unwrap();
+        assert_eq!(index.cycles.len(), 2);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(cycles[0].id.as_str()));
+        assert!(storage.is_dir(&cycles[1].folder_path));
+        assert!(storage.trashed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn archive_cycle_moves_the_selection_to_a_visible_cycle() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+
+        archive_cycle(&storage, &cycles[1].id, true).unwrap();
+        let index = archive_cycle(&storage, &cycles[0].id, true).unwrap();
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(cycles[2].id.as_str()));
+        assert!(index.cycles[0].archived && index.cycles[1].archived && !index.cycles[2].archived);
+
+        let index = archive_cycle(&storage, &cycles[0].id, false).unwrap();
+        assert!(!index.cycles[0].archived);
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(cycles[2].id.as_str()));
+        assert_eq!(archive_cycle(&storage, "cycle_unknown", true).unwrap_err().code, ErrorCode::CycleNotFound);
+    }
+
+    #[test]
+    fn rename_cycle_renames_the_data_and_optionally_the_folder() {
+        let storage = storage_with_parent();
+        let cycle = three_cycles(&storage).remove(0);
+        let watchers = CycleWatchers::default();
+
+        let index = rename_cycle(&storage, &watchers, &cycle.id, "  Autumn  ", false).unwrap();
+        assert_eq!((index.cycles[0].name.as_str(), &index.cycles[0].folder_path), ("Autumn", &cycle.folder_path));
+        assert_eq!(storage.cycle(&cycle.folder_path).unwrap().name, "Autumn");
+
+        let index = rename_cycle(&storage, &watchers, &cycle.id, "Autumn", true).unwrap();
+        let renamed = index.cycles[0].clone();
+        assert_eq!(Path::new(&renamed.folder_path).parent(), Some(Path::new(PARENT)));
+        assert!(renamed.folder_path.contains("/Autumn_"));
+        assert!(!storage.is_dir(&cycle.folder_path));
+        assert_eq!(storage.cycle(&renamed.folder_path).unwrap().name, "Autumn");
+
+        // A folder already named after the cycle stays where it is.
+        let index = rename_cycle(&storage, &watchers, &cycle.id, "Autumn", true).unwrap();
+        assert_eq!(index.cycles[0].folder_path, renamed.folder_path);
+        let error = rename_cycle(&storage, &watchers, &cycle.id, "   ", true).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn rename_cycle_skips_folder_names_that_are_taken() {
+        let storage = storage_with_parent();
+        let cycle = three_cycles(&storage).remove(0);
+        let taken = format!("{PARENT}/{}", cycle_folder_name("Autumn", &cycle.id));
+        storage.add_folder(&taken, None);
+
+        let index = rename_cycle(&storage, &CycleWatchers::default(), &cycle.id, "Autumn", true).unwrap();
+        assert_eq!(index.cycles[0].folder_path, format!("{taken}-2"));
+        assert!(storage.cycle(&taken).is_none());
+    }
+
+    #[test]
+    fn rename_cycle_moves_the_folder_back_when_the_index_cannot_be_written() {
+        let storage = storage_with_parent();
+        let cycle = three_cycles(&storage).remove(0);
+        *storage.fail_index_writes.lock().unwrap() = true;
+
+        assert!(rename_cycle(&storage, &CycleWatchers::default(), &cycle.id, "Autumn", true).is_err());
+        assert_eq!(storage.cycle(&cycle.folder_path).unwrap().name, "A");
+        let index = load_index(&storage).unwrap();
+        assert_eq!((index.cycles[0].name.as_str(), &index.cycles[0].folder_path), ("A", &cycle.folder_path));
+        assert!(storage.is_dir(&cycle.folder_path));
+    }
+
+    #[test]
+    fn relocate_cycle_only_accepts_a_folder_holding_that_cycle() {
+        let storage = storage_with_parent();
+        let cycles = three_cycles(&storage);
+        let moved = storage.cycle(&cycles[0].folder_path).unwrap();
+        storage.remove_folder(&cycles[0].folder_path);
+        storage.add_folder("/moved/A", Some(moved));
+        storage.add_folder("/moved/other", Some(cycle_data("cycle_other", "Other")));
+        let watchers = CycleWatchers::default();
+
+        let error = relocate_cycle(&storage, &watchers, &cycles[0].id, "/moved/other").unwrap_err();
+        assert_eq!((error.code, error.path.as_deref()), (ErrorCode::InvalidFolder, Some("/moved/other")));
+        let error = relocate_cycle(&storage, &watchers, &cycles[0].id, "/moved/empty").unwrap_err();
+        assert_eq!(error.code, ErrorCode::CycleDataNotFound);
+        assert!(load_index(&storage).unwrap().cycles[0].missing);
+
+        let index = relocate_cycle(&storage, &watchers, &cycles[0].id, "/moved/A").unwrap();
+        assert_eq!(index.cycles[0].folder_path, "/moved/A");
+        let index = load_index(&storage).unwrap();
+        assert!(!index.cycles[0].missing);
+        assert_eq!(load_cycle_data(&storage, &cycles[0].id).unwrap().name, "A");
+    }
+
+    fn rollover_source(storage: &MemoryStorage) -> CycleMeta {
+        let cycle = create_cycle(storage, "Q3".to_string(), PARENT).unwrap().cycles.remove(0);
+        save_cycle_data(storage, &CycleWatchers::default(), &cycle.id, cycle_data(&cycle.id, "Q3"), None).unwrap();
+        *storage.edits.lock().unwrap() = 0;
+        *storage.snapshots.lock().unwrap() = 0;
+        cycle
+    }
+
+    fn move_mode() -> RolloverOptions {
+        serde_json::from_value(serde_json::json!({ "mode": "move" })).unwrap()
+    }
+
+    #[test]
+    fn rollover_cycle_moves_open_work_and_closes_the_source() {
+        let storage = storage_with_parent();
+        let source = rollover_source(&storage);
+        let watchers = CycleWatchers::default();
+        let options = move_mode();
+        let rollover = |name: &str| rollover_cycle(&storage, &watchers, &source.id, name.to_string(), PARENT, &options);
+
+        let index = rollover("Q4").unwrap();
+        let next = index.cycles[1].clone();
+        assert!(index.cycles[0].closed_at.is_some());
+        assert_eq!(index.selected_cycle_id.as_deref(), Some(next.id.as_str()));
+        assert_eq!(load_cycle_data(&storage, &next.id).unwrap().works.len(), 1);
+        assert!(load_cycle_data(&storage, &source.id).unwrap().works.is_empty());
+        assert_eq!((*storage.edits.lock().unwrap(), *storage.snapshots.lock().unwrap()), (1, 2));
+        assert!(storage.sync_base(&source.id).unwrap().works.is_empty());
+
+        assert_eq!(rollover("Q5").unwrap_err().code, ErrorCode::InvalidInput);
+    }
+
+    #[test]
+    fn rollover_cycle_leaves_nothing_behind_when_the_index_cannot_be_written() {
+        let storage = storage_with_parent();
+        let source = rollover_source(&storage);
+        let base = storage.sync_base(&source.id);
+        *storage.fail_index_writes.lock().unwrap() = true;
+
+        let watchers = CycleWatchers::default();
+        let result = rollover_cycle(&storage, &watchers, &source.id, "Q4".to_string(), PARENT, &move_mode());
+        assert_eq!(result.unwrap_err().code, ErrorCode::Io);
+        let index = load_index(&storage).unwrap();
+        assert_eq!(index.cycles.len(), 1);
+        assert!(index.cycles[0].closed_at.is_none());
+        assert_eq!(load_cycle_data(&storage, &source.id).unwrap().works.len(), 1);
+        assert_eq!(storage.sync_base(&source.id).map(|b| b.works.len()), base.map(|b| b.works.len()));
+        assert_eq!((*storage.edits.lock().unwrap(), *storage.snapshots.lock().unwrap()), (0, 0));
+        assert_eq!(storage.folders(), [PARENT.to_string(), source.folder_path]);
+    }
+
+    #[test]
+    fn a_failed_rollback_is_reported_with_the_original_error() {
+        let source = serde_json::from_value::<CycleMeta>(serde_json::json!({
+            "id": "cycle_a", "name": "Q3", "createdAt": "2026-07-01T00:00:00Z", "folderPath": "/cycles/Q3"
+        }))
+        .unwrap();
+        let error = rollback_failed(
+            AppError::io("/index.json", "write index error: disk full"),
+            AppError::io("/cycles/Q3", "write cycle data error: disk full"),
+            &source,
+        );
+        assert_eq!((error.code, error.path.as_deref()), (ErrorCode::Io, Some("/index.json")));
+        assert_eq!(error.cycle_id.as_deref(), Some("cycle_a"));
+        assert!(error.message.starts_with("write index error: disk full Cycle \"Q3\" could not be restored"));
+        assert!(error.message.ends_with("write cycle data error: disk full"));
+    }
+
+    #[test]
+    fn filesystem_storage_works_in_any_directory() {
+        let root = std::env::temp_dir().join(format!("cycle-planner-test-{:x}", rand::random::<u64>()));
+        let parent = root.join("cycles");
+        std::fs::create_dir_all(&parent).unwrap();
+        let storage = FsStorage::new(root.join("app"));
+        let watchers = CycleWatchers::default();
+
+        let index = create_cycle(&storage, "Disk".to_string(), &parent.to_string_lossy()).unwrap();
+        let cycle = index.cycles[0].clone();
+        assert!(root.join("app").join("index.json").is_file());
+        assert!(Path::new(&cycle.folder_path).join("cycle_data.json").is_file());
+
+        save_cycle_data(&storage, &watchers, &cycle.id, cycle_data(&cycle.id, "Disk"), None).unwrap();
+        assert_eq!(load_cycle_data(&storage, &cycle.id).unwrap().works.len(), 1);
+        assert_eq!(load_index(&storage).unwrap().cycles.len(), 1);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
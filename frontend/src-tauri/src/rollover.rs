@@ -0,0 +1,201 @@
+use crate::duplicate::{DuplicateOptions, EntityCopier};
+use crate::models::{CycleData, CycleMeta, EntityOrigin, WorkStatus};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloverMode {
+    /// The closed cycle keeps its items; the new cycle gets copies.
+    #[default]
+    Copy,
+    /// Carried items are removed from the closed cycle. A Work that has both completed and open Tasks
+    /// is split: it stays behind with its completed Tasks under its own id, and the new cycle gets a
+    /// copy with a new id (linked by `rolledOverFrom`) that holds the open ones. A Goal that still has
+    /// Works stays behind the same way.
+    Move,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloverOptions {
+    #[serde(default)]
+    pub mode: RolloverMode,
+}
+
+pub struct RolloverPlan {
+    /// Contents of the new cycle.
+    pub next: CycleData,
+    /// Contents the closed cycle should be saved with, when `RolloverMode::Move` changed it.
+    pub remaining: Option<CycleData>,
+}
+
+pub fn plan_rollover(source: &CycleData, target: &CycleMeta, options: &RolloverOptions) -> RolloverPlan {
+    let carried_works = source
+        .works
+        .iter()
+        .filter(|w| w.status != WorkStatus::Done)
+        .map(|w| w.id.as_str())
+        .collect::<HashSet<_>>();
+    let carried_goals = source
+        .works
+        .iter()
+        .filter(|w| carried_works.contains(w.id.as_str()))
+        .filter_map(|w| w.goal_id.as_deref())
+        .collect::<HashSet<_>>();
+    let is_carried_task = |work_id: &str, done: bool| !done && carried_works.contains(work_id);
+
+    let origin = |id: &str| {
+        Some(EntityOrigin {
+            cycle_id: source.id.clone(),
+            id: id.to_string(),
+        })
+    };
+    let copy_options = DuplicateOptions::default();
+    let mut copier = EntityCopier::new(&target.id, &copy_options);
+    let mut next = CycleData::empty(target);
+    for goal in source.goals.iter().filter(|g| carried_goals.contains(g.id.as_str())) {
+        let mut copy = copier.copy_goal(goal);
+        copy.rolled_over_from = origin(&goal.id);
+        next.goals.push(copy);
+    }
+    for work in source.works.iter().filter(|w| carried_works.contains(w.id.as_str())) {
+        let mut copy = copier.copy_work(work);
+        copy.rolled_over_from = origin(&work.id);
+        next.works.push(copy);
+    }
+    for task in source.tasks.iter().filter(|t| is_carried_task(&t.work_id, t.done)) {
+        if let Some(mut copy) = copier.copy_task(task) {
+            copy.rolled_over_from = origin(&task.id);
+            next.tasks.push(copy);
+        }
+    }
+
+    if options.mode == RolloverMode::Copy {
+        return RolloverPlan { next, remaining: None };
+    }
+
+    // Open Tasks moved into the copies above; whatever still has completed Tasks is the half of a
+    // split Work that stays here.
+    let mut remaining = source.clone();
+    remaining.tasks.retain(|t| !is_carried_task(&t.work_id, t.done));
+    let works_with_history = remaining
+        .tasks
+        .iter()
+        .map(|t| t.work_id.clone())
+        .collect::<HashSet<_>>();
+    remaining
+        .works
+        .retain(|w| !carried_works.contains(w.id.as_str()) || works_with_history.contains(&w.id));
+    let goals_in_use = remaining
+        .works
+        .iter()
+        .filter_map(|w| w.goal_id.clone())
+        .collect::<HashSet<_>>();
+    remaining
+        .goals
+        .retain(|g| !carried_goals.contains(g.id.as_str()) || goals_in_use.contains(&g.id));
+
+    RolloverPlan {
+        next,
+        remaining: Some(remaining),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Goal, StorageKind, Task, Work};
+
+    fn meta(id: &str) -> CycleMeta {
+        CycleMeta {
+            id: id.to_string(),
+            name: id.to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            folder_path: format!("/cycles/{id}"),
+            archived: false,
+            missing: false,
+            closed_at: None,
+            storage: StorageKind::Json,
+        }
+    }
+
+    fn work(id: &str, status: WorkStatus) -> Work {
+        Work {
+            id: id.to_string(),
+            cycle_id: "old".to_string(),
+            goal_id: Some("g1".to_string()),
+            status,
+            ..Work::default()
+        }
+    }
+
+    fn task(id: &str, work_id: &str, done: bool) -> Task {
+        Task {
+            id: id.to_string(),
+            cycle_id: "old".to_string(),
+            work_id: work_id.to_string(),
+            done,
+            ..Task::default()
+        }
+    }
+
+    fn source() -> CycleData {
+        let mut data = CycleData::empty(&meta("old"));
+        data.goals.push(Goal {
+            id: "g1".to_string(),
+            cycle_id: "old".to_string(),
+            ..Goal::default()
+        });
+        data.works = vec![
+            work("w-split", WorkStatus::InProgress),
+            work("w-open", WorkStatus::NotStarted),
+            work("w-done", WorkStatus::Done),
+        ];
+        data.tasks = vec![
+            task("t-finished", "w-split", true),
+            task("t-open", "w-split", false),
+            task("t-todo", "w-open", false),
+            task("t-closed", "w-done", true),
+        ];
+        data
+    }
+
+    fn ids<'a>(items: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+        let mut ids = items.collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn copy_mode_leaves_the_closed_cycle_alone() {
+        let plan = plan_rollover(&source(), &meta("new"), &RolloverOptions::default());
+        assert!(plan.remaining.is_none());
+        assert_eq!(plan.next.works.len(), 2);
+        assert_eq!(plan.next.tasks.len(), 2);
+        assert!(plan.next.tasks.iter().all(|t| !t.done && t.cycle_id == "new"));
+    }
+
+    #[test]
+    fn move_mode_splits_a_work_between_done_and_open_tasks() {
+        let options = RolloverOptions { mode: RolloverMode::Move };
+        let plan = plan_rollover(&source(), &meta("new"), &options);
+        let remaining = plan.remaining.expect("move mode changes the closed cycle");
+
+        assert_eq!(ids(remaining.works.iter().map(|w| w.id.as_str())), ["w-done", "w-split"]);
+        assert_eq!(ids(remaining.tasks.iter().map(|t| t.id.as_str())), ["t-closed", "t-finished"]);
+        assert_eq!(remaining.goals.len(), 1);
+
+        let split = plan
+            .next
+            .works
+            .iter()
+            .find(|w| w.rolled_over_from.as_ref().map(|o| o.id.as_str()) == Some("w-split"))
+            .expect("the open half of the split Work is carried");
+        assert_ne!(split.id, "w-split");
+        let carried = plan.next.tasks.iter().filter(|t| t.work_id == split.id).collect::<Vec<_>>();
+        assert_eq!(carried.len(), 1);
+        assert_eq!(carried[0].rolled_over_from.as_ref().map(|o| o.id.as_str()), Some("t-open"));
+        assert!(plan.next.works.iter().all(|w| w.id != "w-done"));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::error::{AppError, ErrorCode};
+use crate::models::IndexData;
+use crate::storage::Storage;
+use crate::{normalize_display_path, read_folder_data};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Deeper scans are clamped so a mistyped root (a drive, a home folder) stays bounded.
+const MAX_SCAN_DEPTH: u32 = 8;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannedCycle {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub folder_path: String,
+    pub registered: bool,
+}
+
+/// Walks `root` up to `depth` levels below it and lists every folder holding readable cycle data
+/// (`cycle_data.json` or `cycle_data.sqlite`). Hidden folders (e.g. `.history`) and symlinks are
+/// not followed, and the walk does not descend into a cycle folder once one is found.
+pub fn scan_for_cycles(
+    storage: &dyn Storage,
+    root: &Path,
+    depth: u32,
+    index: &IndexData,
+) -> Result<Vec<ScannedCycle>, AppError> {
+    if !root.is_dir() {
+        return Err(AppError::new(ErrorCode::InvalidFolder, "The selected folder is not valid.").with_path(root));
+    }
+
+    let mut found = vec![];
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, level)) = pending.pop() {
+        if let Ok(Some((data, _))) = read_folder_data(storage, &dir.to_string_lossy()) {
+            found.push(ScannedCycle {
+                registered: index.cycles.iter().any(|c| c.id == data.id),
+                id: data.id,
+                name: data.name,
+                created_at: data.created_at,
+                folder_path: normalize_display_path(&dir.to_string_lossy()),
+            });
+            continue;
+        }
+
+        if level >= depth.min(MAX_SCAN_DEPTH) {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if is_dir && !hidden {
+                pending.push((entry.path(), level + 1));
+            }
+        }
+    }
+
+    found.sort_by(|a, b| a.folder_path.cmp(&b.folder_path));
+    Ok(found)
+}
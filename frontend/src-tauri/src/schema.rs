@@ -0,0 +1,180 @@
+use crate::dates::normalize_timestamp;
+use crate::error::{AppError, ErrorCode};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+
+pub const INDEX_SCHEMA_VERSION: u32 = 2;
+pub const CYCLE_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades a document from version `n` to `n + 1`; `MIGRATIONS[n]` is the step for version `n`.
+type Migration = fn(&mut Map<String, Value>);
+
+const INDEX_MIGRATIONS: &[Migration] = &[index_v0_to_v1, index_v1_to_v2];
+const CYCLE_MIGRATIONS: &[Migration] = &[cycle_v0_to_v1, cycle_v1_to_v2];
+
+fn ensure_array(doc: &mut Map<String, Value>, key: &str) {
+    if !doc.get(key).is_some_and(Value::is_array) {
+        doc.insert(key.to_string(), Value::Array(vec![]));
+    }
+}
+
+/// v0 is every index written before the field existed.
+fn index_v0_to_v1(doc: &mut Map<String, Value>) {
+    ensure_array(doc, "cycles");
+}
+
+/// v0 is every cycle_data.json written before the field existed.
+fn cycle_v0_to_v1(doc: &mut Map<String, Value>) {
+    ensure_array(doc, "goals");
+    ensure_array(doc, "works");
+    ensure_array(doc, "tasks");
+}
+
+/// Rewrites `doc[key]` as RFC 3339 when it holds Unix seconds, as a string or a number.
+fn normalize_timestamp_field(doc: &mut Map<String, Value>, key: &str) {
+    let raw = match doc.get(key) {
+        Some(Value::String(value)) => value.clone(),
+        Some(Value::Number(value)) => value.to_string(),
+        _ => return,
+    };
+    doc.insert(key.to_string(), Value::String(normalize_timestamp(&raw)));
+}
+
+/// v1 stored `createdAt` and `closedAt` as Unix seconds.
+fn index_v1_to_v2(doc: &mut Map<String, Value>) {
+    let Some(Value::Array(cycles)) = doc.get_mut("cycles") else {
+        return;
+    };
+    for cycle in cycles.iter_mut().filter_map(Value::as_object_mut) {
+        normalize_timestamp_field(cycle, "createdAt");
+        normalize_timestamp_field(cycle, "closedAt");
+    }
+}
+
+/// v1 stored `createdAt` as Unix seconds.
+fn cycle_v1_to_v2(doc: &mut Map<String, Value>) {
+    normalize_timestamp_field(doc, "createdAt");
+}
+
+/// A version too large for `u32` can only come from a newer or corrupt file, so it reads as the
+/// largest version instead of wrapping around to one this app supports.
+fn schema_version_of(doc: &Map<String, Value>) -> u32 {
+    doc.get("schemaVersion")
+        .and_then(Value::as_u64)
+        .map(|v| u32::try_from(v).unwrap_or(u32::MAX))
+        .unwrap_or(0)
+}
+
+pub fn newer_version_error(label: &str, found: u32, supported: u32) -> AppError {
+    AppError::new(
+        ErrorCode::NewerSchema,
+        format!(
+            "{label} uses schema version {found}, but this version of Cycle only supports up to {supported}. Update the app to open it."
+        ),
+    )
+}
+
+fn migrate(raw: &str, label: &str, current: u32, migrations: &[Migration]) -> Result<Value, AppError> {
+    let value =
+        serde_json::from_str::<Value>(raw).map_err(|e| AppError::corrupt(format!("parse {label} error: {e}")))?;
+    migrate_value(value, label, current, migrations)
+}
+
+fn migrate_value(mut value: Value, label: &str, current: u32, migrations: &[Migration]) -> Result<Value, AppError> {
+    let doc = value
+        .as_object_mut()
+        .ok_or_else(|| AppError::corrupt(format!("parse {label} error: expected a JSON object")))?;
+
+    let mut version = schema_version_of(doc);
+    if version > current {
+        return Err(newer_version_error(label, version, current));
+    }
+    while version < current {
+        migrations[version as usize](doc);
+        version += 1;
+        doc.insert("schemaVersion".to_string(), Value::from(version));
+    }
+    Ok(value)
+}
+
+pub fn migrate_index(raw: &str) -> Result<Value, AppError> {
+    migrate(raw, "index", INDEX_SCHEMA_VERSION, INDEX_MIGRATIONS)
+}
+
+pub fn migrate_cycle_data(raw: &str) -> Result<Value, AppError> {
+    migrate(raw, "cycle data", CYCLE_SCHEMA_VERSION, CYCLE_MIGRATIONS)
+}
+
+/// Same as `migrate_cycle_data` for a document that was not read from JSON text.
+pub fn migrate_cycle_value(value: Value) -> Result<Value, AppError> {
+    migrate_value(value, "cycle data", CYCLE_SCHEMA_VERSION, CYCLE_MIGRATIONS)
+}
+
+/// Fails when the file at `path` was written by a newer schema than `supported`. Unreadable or
+/// unparsable files are left for the regular load path to report.
+pub fn ensure_not_newer(path: &Path, label: &str, supported: u32) -> Result<(), AppError> {
+    let Ok(raw) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    let Ok(Value::Object(doc)) = serde_json::from_str::<Value>(&raw) else {
+        return Ok(());
+    };
+    let found = schema_version_of(&doc);
+    if found > supported {
+        return Err(newer_version_error(label, found, supported).with_path(path));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_version_past_u32_is_newer_instead_of_wrapping() {
+        let wrapped = u64::from(u32::MAX) + 1 + u64::from(INDEX_SCHEMA_VERSION);
+        let raw = format!(r#"{{"schemaVersion": {wrapped}, "cycles": []}}"#);
+        let error = migrate_index(&raw).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NewerSchema);
+    }
+
+    #[test]
+    fn index_v1_timestamps_become_rfc3339() {
+        let raw = r#"{"schemaVersion": 1, "cycles": [
+            {"id": "a", "createdAt": "1700000000", "closedAt": 1700000000123},
+            {"id": "b", "createdAt": "2024-01-01T00:00:00+09:00"},
+            {"id": "c", "createdAt": "garbage"}
+        ]}"#;
+        let migrated = migrate_index(raw).unwrap();
+        assert_eq!(migrated["schemaVersion"], 2);
+        assert_eq!(migrated["cycles"][0]["createdAt"], "2023-11-14T22:13:20Z");
+        assert_eq!(migrated["cycles"][0]["closedAt"], "2023-11-14T22:13:20.123Z");
+        assert_eq!(migrated["cycles"][1]["createdAt"], "2024-01-01T00:00:00+09:00");
+        assert_eq!(migrated["cycles"][1].get("closedAt"), None);
+        assert_eq!(migrated["cycles"][2]["createdAt"], "garbage");
+    }
+
+    #[test]
+    fn cycle_v1_created_at_becomes_rfc3339() {
+        let migrated = migrate_cycle_data(r#"{"schemaVersion": 1, "id": "c1", "createdAt": 1700000000}"#).unwrap();
+        assert_eq!(migrated["schemaVersion"], 2);
+        assert_eq!(migrated["createdAt"], "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn a_current_document_is_left_alone() {
+        let raw = r#"{"schemaVersion": 2, "id": "c1", "createdAt": "1700000000", "goals": [], "works": [], "tasks": []}"#;
+        let migrated = migrate_cycle_data(raw).unwrap();
+        assert_eq!(migrated, serde_json::from_str::<Value>(raw).unwrap());
+    }
+
+    #[test]
+    fn a_missing_version_migrates_from_zero() {
+        let migrated = migrate_cycle_data(r#"{"id": "c1"}"#).unwrap();
+        assert_eq!(migrated["schemaVersion"], CYCLE_SCHEMA_VERSION);
+        assert_eq!(migrated["goals"], Value::Array(vec![]));
+        assert_eq!(migrated["works"], Value::Array(vec![]));
+        assert_eq!(migrated["tasks"], Value::Array(vec![]));
+    }
+}
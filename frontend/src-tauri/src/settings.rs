@@ -0,0 +1,311 @@
+use crate::atomic_file::{read_with_backup, write_json_atomic};
+use crate::error::AppError;
+use crate::i18n::Locale;
+use crate::schema::{ensure_not_newer, newer_version_error};
+use crate::window_mode::WindowMode;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+pub const MIN_WINDOW_OPACITY: f64 = 0.5;
+pub const MAX_WINDOW_OPACITY: f64 = 1.0;
+const MAX_REMINDER_LEAD_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ReminderSettings {
+    pub enabled: bool,
+    /// Days before a task's due date at which reminding starts; 0 reminds on the day itself.
+    pub lead_days: u32,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lead_days: 1,
+        }
+    }
+}
+
+/// Preferences kept in `settings.json` in the data folder. Missing fields take their default and
+/// fields this version does not know are kept in `extra`, so older and newer apps can share it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub schema_version: u32,
+    /// Language chosen in the app; `None` follows the system locale.
+    pub locale: Option<Locale>,
+    pub window_opacity: f64,
+    pub always_on_top: bool,
+    pub window_mode: WindowMode,
+    pub reminders: ReminderSettings,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_version: SETTINGS_SCHEMA_VERSION,
+            locale: None,
+            window_opacity: MAX_WINDOW_OPACITY,
+            always_on_top: false,
+            window_mode: WindowMode::Normal,
+            reminders: ReminderSettings::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(MIN_WINDOW_OPACITY..=MAX_WINDOW_OPACITY).contains(&self.window_opacity) {
+            return Err(AppError::invalid_input(format!(
+                "Window opacity must be between {MIN_WINDOW_OPACITY} and {MAX_WINDOW_OPACITY}."
+            )));
+        }
+        if self.reminders.lead_days > MAX_REMINDER_LEAD_DAYS {
+            return Err(AppError::invalid_input(format!(
+                "Reminders can start at most {MAX_REMINDER_LEAD_DAYS} days before the due date."
+            )));
+        }
+        Ok(())
+    }
+
+    /// Brings values edited by hand back into range instead of refusing the whole file.
+    fn sanitized(mut self) -> Self {
+        self.window_opacity = if self.window_opacity.is_nan() {
+            MAX_WINDOW_OPACITY
+        } else {
+            self.window_opacity.clamp(MIN_WINDOW_OPACITY, MAX_WINDOW_OPACITY)
+        };
+        self.reminders.lead_days = self.reminders.lead_days.min(MAX_REMINDER_LEAD_DAYS);
+        self
+    }
+}
+
+/// Applies an RFC 7386 JSON merge patch: objects merge key by key and `null` removes a key,
+/// which resets that setting to its default.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn patched(current: &Settings, patch: &Value) -> Result<Settings, AppError> {
+    if !patch.is_object() {
+        return Err(AppError::invalid_input("Settings changes must be a JSON object."));
+    }
+    let mut value =
+        serde_json::to_value(current).map_err(|e| AppError::internal(format!("serialize settings error: {e}")))?;
+    merge_patch(&mut value, patch);
+    let mut next = serde_json::from_value::<Settings>(value)
+        .map_err(|e| AppError::invalid_input(format!("Invalid settings: {e}")))?;
+    next.schema_version = SETTINGS_SCHEMA_VERSION;
+    next.validate()?;
+    Ok(next)
+}
+
+fn parse_settings(raw: &str) -> Result<Settings, AppError> {
+    let mut value =
+        serde_json::from_str::<Value>(raw).map_err(|e| AppError::corrupt(format!("parse settings error: {e}")))?;
+    if let Value::Object(doc) = &mut value {
+        let version = doc.get("schemaVersion").and_then(Value::as_u64).unwrap_or(0);
+        if version > u64::from(SETTINGS_SCHEMA_VERSION) {
+            let found = u32::try_from(version).unwrap_or(u32::MAX);
+            return Err(newer_version_error("settings", found, SETTINGS_SCHEMA_VERSION));
+        }
+        // A window mode this version does not know (from a newer version or edited by hand) opens
+        // the normal window instead of discarding the whole file. Commands and patches still
+        // reject unknown modes.
+        if doc.get("windowMode").and_then(Value::as_str).and_then(WindowMode::from_key).is_none() {
+            doc.remove("windowMode");
+        }
+    }
+    let settings = serde_json::from_value::<Settings>(value)
+        .map_err(|e| AppError::corrupt(format!("parse settings error: {e}")))?;
+    Ok(settings.sanitized())
+}
+
+/// The loaded settings and the file they are saved to. Every change goes through `update` or
+/// `apply_patch`, which validate the result and save it before it becomes current.
+pub struct SettingsStore {
+    path: PathBuf,
+    current: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// Loads the settings in `root`, falling back to the defaults when the file is missing or
+    /// unreadable so a broken file never keeps the app from starting. A file saved by a newer
+    /// version also starts from the defaults but is never overwritten (see `ensure_writable`).
+    pub fn open(root: &Path) -> Self {
+        let store = Self {
+            path: root.join(SETTINGS_FILE),
+            current: Mutex::new(Settings::default()),
+        };
+        store.reload();
+        store
+    }
+
+    fn load(&self) -> Settings {
+        if ensure_not_newer(&self.path, "settings", SETTINGS_SCHEMA_VERSION).is_err() {
+            return Settings::default();
+        }
+        read_with_backup(&self.path, "settings", parse_settings)
+            .ok()
+            .flatten()
+            .unwrap_or_default()
+    }
+
+    /// Reads the file again, e.g. after it was copied in from another data folder.
+    pub fn reload(&self) -> Settings {
+        let loaded = self.load();
+        if let Ok(mut current) = self.current.lock() {
+            *current = loaded.clone();
+        }
+        loaded
+    }
+
+    /// Fails with `NewerSchema` while the file was saved by a newer version. Commands check this
+    /// before they change the window, so they never apply a change that cannot be saved.
+    pub fn ensure_writable(&self) -> Result<(), AppError> {
+        ensure_not_newer(&self.path, "settings", SETTINGS_SCHEMA_VERSION)
+    }
+
+    pub fn get(&self) -> Result<Settings, AppError> {
+        self.current
+            .lock()
+            .map(|current| current.clone())
+            .map_err(|_| AppError::internal("Failed to read settings."))
+    }
+
+    pub fn update(&self, change: impl FnOnce(&mut Settings)) -> Result<Settings, AppError> {
+        let mut current = self
+            .current
+            .lock()
+            .map_err(|_| AppError::internal("Failed to update settings."))?;
+        let mut next = current.clone();
+        change(&mut next);
+        self.commit(&mut current, next)
+    }
+
+    /// The validated settings `patch` would produce, without saving them, so they can be applied
+    /// to the app before they are saved.
+    pub fn preview_patch(&self, patch: &Value) -> Result<Settings, AppError> {
+        patched(&self.get()?, patch)
+    }
+
+    /// Merges `patch` (see `merge_patch`) into the current settings.
+    pub fn apply_patch(&self, patch: &Value) -> Result<Settings, AppError> {
+        let mut current = self
+            .current
+            .lock()
+            .map_err(|_| AppError::internal("Failed to update settings."))?;
+        let next = patched(&current, patch)?;
+        self.commit(&mut current, next)
+    }
+
+    fn commit(&self, current: &mut Settings, mut next: Settings) -> Result<Settings, AppError> {
+        next.schema_version = SETTINGS_SCHEMA_VERSION;
+        next.validate()?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AppError::io(dir, format!("create app dir error: {e}")))?;
+        }
+        self.ensure_writable()?;
+        write_json_atomic(&self.path, &next, "settings")?;
+        *current = next.clone();
+        Ok(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use serde_json::json;
+
+    #[test]
+    fn an_unknown_window_mode_in_the_file_opens_the_normal_window() {
+        let settings = parse_settings(r#"{"schemaVersion": 1, "windowMode": "postit", "alwaysOnTop": true}"#).unwrap();
+        assert_eq!(settings.window_mode, WindowMode::Normal);
+        assert!(settings.always_on_top);
+    }
+
+    #[test]
+    fn an_unknown_window_mode_in_a_patch_is_rejected() {
+        let error = patched(&Settings::default(), &json!({ "windowMode": "postit" })).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidInput);
+        let next = patched(&Settings::default(), &json!({ "windowMode": "postIt" })).unwrap();
+        assert_eq!(next.window_mode, WindowMode::PostIt);
+    }
+
+    #[test]
+    fn a_newer_settings_file_is_refused() {
+        let error = parse_settings(r#"{"schemaVersion": 2}"#).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NewerSchema);
+    }
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("settings-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    #[test]
+    fn a_store_saves_changes_and_reloads_them() {
+        let folder = temp_folder();
+        let store = SettingsStore::open(&folder);
+        store.update(|settings| settings.always_on_top = true).unwrap();
+        store.apply_patch(&json!({ "windowMode": "calendar" })).unwrap();
+
+        let reopened = SettingsStore::open(&folder).get().unwrap();
+        assert!(reopened.always_on_top);
+        assert_eq!(reopened.window_mode, WindowMode::Calendar);
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn a_store_never_overwrites_a_newer_settings_file() {
+        let folder = temp_folder();
+        let newer = r#"{"schemaVersion": 2, "alwaysOnTop": true, "theme": "dark"}"#;
+        fs::write(folder.join(SETTINGS_FILE), newer).unwrap();
+
+        let store = SettingsStore::open(&folder);
+        assert!(!store.get().unwrap().always_on_top);
+        assert_eq!(store.ensure_writable().unwrap_err().code, ErrorCode::NewerSchema);
+        let error = store.update(|settings| settings.window_opacity = 0.8).unwrap_err();
+        assert_eq!(error.code, ErrorCode::NewerSchema);
+        assert_eq!(store.apply_patch(&json!({ "alwaysOnTop": true })).unwrap_err().code, ErrorCode::NewerSchema);
+        assert_eq!(store.get().unwrap().window_opacity, Settings::default().window_opacity);
+        assert_eq!(fs::read_to_string(folder.join(SETTINGS_FILE)).unwrap(), newer);
+        let _ = fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn hand_edited_values_are_brought_back_into_range() {
+        let settings = parse_settings(r#"{"windowOpacity": 0.1, "reminders": {"leadDays": 90}}"#).unwrap();
+        assert_eq!(settings.window_opacity, MIN_WINDOW_OPACITY);
+        assert_eq!(settings.reminders.lead_days, MAX_REMINDER_LEAD_DAYS);
+    }
+}
@@ -0,0 +1,317 @@
+use crate::atomic_file::{read_with_backup, write_json_atomic};
+use crate::cycle_store::{self, store_for};
+use crate::error::AppError;
+use crate::models::{CycleData, IndexData, StorageKind};
+use crate::schema::{ensure_not_newer, migrate_index, INDEX_SCHEMA_VERSION};
+use crate::watch::file_revision;
+use crate::{history, journal, merge, read_cycle_file};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Everything the cycle commands persist: the index, the data inside each cycle folder and the
+/// bookkeeping kept next to it. Commands only talk to this trait, so their logic runs the same
+/// against the real app data folder and against memory in tests.
+pub trait Storage: Sync {
+    /// The index as stored, already migrated to the current schema; `None` before the first save.
+    fn read_index(&self) -> Result<Option<IndexData>, AppError>;
+    fn write_index(&self, index: &IndexData) -> Result<(), AppError>;
+
+    fn is_dir(&self, path: &str) -> bool;
+    /// Whether anything, a file or a folder, is at `path`.
+    fn exists(&self, path: &str) -> bool;
+    fn create_dir(&self, path: &str) -> Result<(), AppError>;
+    fn rename_dir(&self, from: &str, to: &str) -> Result<(), AppError>;
+    /// Deletes a folder the app created and is abandoning, with everything in it.
+    fn remove_dir(&self, path: &str) -> Result<(), AppError>;
+    /// Moves a cycle folder to the system trash.
+    fn trash_dir(&self, path: &str) -> Result<(), AppError>;
+
+    /// Storage kind of the cycle data found in `folder`, if there is any.
+    fn detect_cycle(&self, folder: &str) -> Option<StorageKind>;
+    fn read_cycle(&self, folder: &str, kind: StorageKind) -> Result<Option<CycleData>, AppError>;
+    fn write_cycle(&self, folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError>;
+    /// Changes whenever the stored cycle data changes, including edits made outside the app.
+    fn cycle_revision(&self, folder: &str, kind: StorageKind) -> Option<String>;
+
+    fn has_conflict_copies(&self, folder: &str) -> bool;
+    fn read_sync_base(&self, cycle_id: &str) -> Result<Option<CycleData>, AppError>;
+    fn write_sync_base(&self, cycle_id: &str, data: &CycleData) -> Result<(), AppError>;
+
+    /// Keeps `data` as a restorable snapshot of the cycle, at the cadence `kind` calls for.
+    fn record_snapshot(&self, folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError>;
+    /// Journals the change from `previous` to `next` so it can be undone.
+    fn record_edit(&self, folder: &str, previous: &CycleData, next: &CycleData) -> Result<(), AppError>;
+}
+
+/// Stores the index and sync bases under `root` (the app data folder in the app) and cycle data
+/// in the cycle folders themselves.
+pub struct FsStorage {
+    root: PathBuf,
+}
+
+impl FsStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn index_file(&self) -> PathBuf {
+        self.root.join("index.json")
+    }
+
+    /// Last version of a cycle this machine saw in sync, kept outside the (possibly shared) cycle
+    /// folder as the base for three-way merges.
+    fn sync_base_file(&self, cycle_id: &str) -> PathBuf {
+        self.root.join("sync_bases").join(format!("{cycle_id}.json"))
+    }
+}
+
+impl Storage for FsStorage {
+    fn read_index(&self) -> Result<Option<IndexData>, AppError> {
+        let path = self.index_file();
+        ensure_not_newer(&path, "index", INDEX_SCHEMA_VERSION)?;
+        read_with_backup(&path, "index", |raw| {
+            serde_json::from_value::<IndexData>(migrate_index(raw)?)
+                .map_err(|e| AppError::corrupt(format!("parse index error: {e}")))
+        })
+    }
+
+    fn write_index(&self, index: &IndexData) -> Result<(), AppError> {
+        let path = self.index_file();
+        fs::create_dir_all(&self.root).map_err(|e| AppError::io(&self.root, format!("create app dir error: {e}")))?;
+        ensure_not_newer(&path, "index", INDEX_SCHEMA_VERSION)?;
+        write_json_atomic(&path, index, "index")
+    }
+
+    fn is_dir(&self, path: &str) -> bool {
+        Path::new(path).is_dir()
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        Path::new(path).exists()
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), AppError> {
+        fs::create_dir_all(path).map_err(|e| AppError::io(path, format!("Failed to create cycle folder: {e}")))
+    }
+
+    fn rename_dir(&self, from: &str, to: &str) -> Result<(), AppError> {
+        fs::rename(from, to).map_err(|e| AppError::io(from, format!("Failed to rename cycle folder: {e}")))
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), AppError> {
+        fs::remove_dir_all(path).map_err(|e| AppError::io(path, format!("Failed to remove cycle folder: {e}")))
+    }
+
+    fn trash_dir(&self, path: &str) -> Result<(), AppError> {
+        trash::delete(path).map_err(|e| AppError::io(path, format!("Failed to move cycle folder to trash: {e}")))
+    }
+
+    fn detect_cycle(&self, folder: &str) -> Option<StorageKind> {
+        cycle_store::detect_storage(Path::new(folder))
+    }
+
+    fn read_cycle(&self, folder: &str, kind: StorageKind) -> Result<Option<CycleData>, AppError> {
+        store_for(kind).read(Path::new(folder))
+    }
+
+    fn write_cycle(&self, folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError> {
+        store_for(kind).write(Path::new(folder), data)
+    }
+
+    fn cycle_revision(&self, folder: &str, kind: StorageKind) -> Option<String> {
+        file_revision(&store_for(kind).data_file(Path::new(folder)))
+    }
+
+    fn has_conflict_copies(&self, folder: &str) -> bool {
+        !merge::find_conflict_copies(Path::new(folder)).is_empty()
+    }
+
+    fn read_sync_base(&self, cycle_id: &str) -> Result<Option<CycleData>, AppError> {
+        read_cycle_file(&self.sync_base_file(cycle_id))
+    }
+
+    fn write_sync_base(&self, cycle_id: &str, data: &CycleData) -> Result<(), AppError> {
+        let path = self.sync_base_file(cycle_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AppError::io(dir, format!("create sync base dir error: {e}")))?;
+        }
+        write_json_atomic(&path, data, "sync base")
+    }
+
+    fn record_snapshot(&self, folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError> {
+        history::record_snapshot(folder, kind, data)
+    }
+
+    fn record_edit(&self, folder: &str, previous: &CycleData, next: &CycleData) -> Result<(), AppError> {
+        journal::record_edit(folder, previous, next)
+    }
+}
+
+#[cfg(test)]
+pub use memory::MemoryStorage;
+
+#[cfg(test)]
+mod memory {
+    use super::Storage;
+    use crate::error::AppError;
+    use crate::models::{CycleData, IndexData, StorageKind};
+    use crate::watch::content_revision;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    /// Keeps everything in memory. Folders only exist once created (or added with `add_folder`),
+    /// and snapshots and undo journal entries are only counted. The `fail_*` switches make the matching
+    /// operation fail, to test what a command leaves behind.
+    #[derive(Default)]
+    pub struct MemoryStorage {
+        index: Mutex<Option<IndexData>>,
+        dirs: Mutex<HashSet<String>>,
+        cycles: Mutex<HashMap<String, (StorageKind, CycleData)>>,
+        sync_bases: Mutex<HashMap<String, CycleData>>,
+        pub edits: Mutex<usize>,
+        pub snapshots: Mutex<usize>,
+        pub trashed: Mutex<Vec<String>>,
+        pub fail_index_writes: Mutex<bool>,
+        pub fail_trash: Mutex<bool>,
+    }
+
+    impl MemoryStorage {
+        /// Puts a cycle folder in place, as if another machine had created it.
+        pub fn add_folder(&self, folder: &str, data: Option<CycleData>) {
+            self.dirs.lock().unwrap().insert(folder.to_string());
+            if let Some(data) = data {
+                self.cycles
+                    .lock()
+                    .unwrap()
+                    .insert(folder.to_string(), (StorageKind::Json, data));
+            }
+        }
+
+        pub fn remove_folder(&self, folder: &str) {
+            self.dirs.lock().unwrap().remove(folder);
+            self.cycles.lock().unwrap().remove(folder);
+        }
+
+        pub fn folders(&self) -> Vec<String> {
+            let mut folders = self.dirs.lock().unwrap().iter().cloned().collect::<Vec<_>>();
+            folders.sort();
+            folders
+        }
+
+        pub fn cycle(&self, folder: &str) -> Option<CycleData> {
+            self.cycles.lock().unwrap().get(folder).map(|(_, data)| data.clone())
+        }
+
+        pub fn sync_base(&self, cycle_id: &str) -> Option<CycleData> {
+            self.sync_bases.lock().unwrap().get(cycle_id).cloned()
+        }
+    }
+
+    impl Storage for MemoryStorage {
+        fn read_index(&self) -> Result<Option<IndexData>, AppError> {
+            Ok(self.index.lock().unwrap().clone())
+        }
+
+        fn write_index(&self, index: &IndexData) -> Result<(), AppError> {
+            if *self.fail_index_writes.lock().unwrap() {
+                return Err(AppError::io("/index.json", "write index error: disk full"));
+            }
+            *self.index.lock().unwrap() = Some(index.clone());
+            Ok(())
+        }
+
+        fn is_dir(&self, path: &str) -> bool {
+            self.dirs.lock().unwrap().contains(path)
+        }
+
+        fn exists(&self, path: &str) -> bool {
+            self.is_dir(path)
+        }
+
+        fn create_dir(&self, path: &str) -> Result<(), AppError> {
+            self.dirs.lock().unwrap().insert(path.to_string());
+            Ok(())
+        }
+
+        fn rename_dir(&self, from: &str, to: &str) -> Result<(), AppError> {
+            if !self.dirs.lock().unwrap().remove(from) {
+                return Err(AppError::io(from, "Failed to rename cycle folder: not found"));
+            }
+            self.dirs.lock().unwrap().insert(to.to_string());
+            let mut cycles = self.cycles.lock().unwrap();
+            if let Some(cycle) = cycles.remove(from) {
+                cycles.insert(to.to_string(), cycle);
+            }
+            Ok(())
+        }
+
+        fn remove_dir(&self, path: &str) -> Result<(), AppError> {
+            self.remove_folder(path);
+            Ok(())
+        }
+
+        fn trash_dir(&self, path: &str) -> Result<(), AppError> {
+            if *self.fail_trash.lock().unwrap() {
+                return Err(AppError::io(path, "Failed to move cycle folder to trash: access denied"));
+            }
+            self.remove_folder(path);
+            self.trashed.lock().unwrap().push(path.to_string());
+            Ok(())
+        }
+
+        fn detect_cycle(&self, folder: &str) -> Option<StorageKind> {
+            self.cycles.lock().unwrap().get(folder).map(|(kind, _)| *kind)
+        }
+
+        fn read_cycle(&self, folder: &str, kind: StorageKind) -> Result<Option<CycleData>, AppError> {
+            let cycles = self.cycles.lock().unwrap();
+            Ok(cycles
+                .get(folder)
+                .filter(|(stored, _)| *stored == kind)
+                .map(|(_, data)| data.clone()))
+        }
+
+        fn write_cycle(&self, folder: &str, kind: StorageKind, data: &CycleData) -> Result<(), AppError> {
+            self.cycles
+                .lock()
+                .unwrap()
+                .insert(folder.to_string(), (kind, data.clone()));
+            Ok(())
+        }
+
+        fn cycle_revision(&self, folder: &str, kind: StorageKind) -> Option<String> {
+            let data = self.read_cycle(folder, kind).ok()??;
+            Some(content_revision(&serde_json::to_vec(&data).ok()?))
+        }
+
+        fn has_conflict_copies(&self, _folder: &str) -> bool {
+            false
+        }
+
+        fn read_sync_base(&self, cycle_id: &str) -> Result<Option<CycleData>, AppError> {
+            Ok(self.sync_base(cycle_id))
+        }
+
+        fn write_sync_base(&self, cycle_id: &str, data: &CycleData) -> Result<(), AppError> {
+            self.sync_bases
+                .lock()
+                .unwrap()
+                .insert(cycle_id.to_string(), data.clone());
+            Ok(())
+        }
+
+        fn record_snapshot(&self, _folder: &str, _kind: StorageKind, _data: &CycleData) -> Result<(), AppError> {
+            *self.snapshots.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        fn record_edit(&self, _folder: &str, _previous: &CycleData, _next: &CycleData) -> Result<(), AppError> {
+            *self.edits.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+}
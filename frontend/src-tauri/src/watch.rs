@@ -0,0 +1,193 @@
+use crate::cycle_store::store_for;
+use crate::error::{AppError, ErrorCode};
+use crate::models::CycleMeta;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+pub const CYCLE_DATA_CHANGED_EVENT: &str = "cycle-data-changed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycleDataChanged {
+    cycle_id: String,
+    revision: Option<String>,
+}
+
+pub fn content_revision(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// SHA-256 of the file contents, or `None` when the file does not exist (yet).
+pub fn file_revision(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| content_revision(&bytes))
+}
+
+/// One watcher per open cycle folder. Each remembers the last revision the backend itself read or
+/// wrote, so only changes made by someone else (a sync client, a script) reach the UI.
+#[derive(Default)]
+pub struct CycleWatchers {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+    known_revisions: Arc<Mutex<HashMap<String, Option<String>>>>,
+}
+
+impl CycleWatchers {
+    fn remember(&self, cycle_id: &str, revision: Option<String>) {
+        if let Ok(mut known) = self.known_revisions.lock() {
+            known.insert(cycle_id.to_string(), revision);
+        }
+    }
+
+    /// Runs a save and records the new revision. The watcher callback waits on the same lock, so
+    /// it never reports the backend's own write as an external change. With `expected_revision`
+    /// the save only runs while the data is still at that revision, so it never overwrites an edit
+    /// made outside the app since the caller loaded it.
+    pub fn guarded_write(
+        &self,
+        cycle_id: &str,
+        expected_revision: Option<String>,
+        revision: impl Fn() -> Option<String>,
+        write: impl FnOnce() -> Result<(), AppError>,
+    ) -> Result<Option<String>, AppError> {
+        let mut known = self
+            .known_revisions
+            .lock()
+            .map_err(|_| AppError::internal("Failed to read cycle revision state."))?;
+        if expected_revision.is_some() && revision() != expected_revision {
+            let message =
+                "The cycle data was changed outside Cycle after it was loaded. Reload the Cycle before saving.";
+            return Err(AppError::new(ErrorCode::ExternalChange, message).with_cycle(cycle_id));
+        }
+        write()?;
+        let revision = revision();
+        known.insert(cycle_id.to_string(), revision.clone());
+        Ok(revision)
+    }
+
+    pub fn watch(&self, app: &tauri::AppHandle, cycle: &CycleMeta) -> Result<(), AppError> {
+        let mut watchers = self
+            .watchers
+            .lock()
+            .map_err(|_| AppError::internal("Failed to update cycle watchers."))?;
+        if watchers.contains_key(&cycle.id) {
+            return Ok(());
+        }
+
+        let file = store_for(cycle.storage).data_file(Path::new(&cycle.folder_path));
+        self.remember(&cycle.id, file_revision(&file));
+
+        let app = app.clone();
+        let cycle_id = cycle.id.clone();
+        let known_revisions = Arc::clone(&self.known_revisions);
+        let watched_file = file.clone();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if !event.paths.iter().any(|p| p.file_name() == watched_file.file_name()) {
+                return;
+            }
+            let revision = file_revision(&watched_file);
+            let Ok(mut known) = known_revisions.lock() else {
+                return;
+            };
+            if known.get(&cycle_id) == Some(&revision) {
+                return;
+            }
+            known.insert(cycle_id.clone(), revision.clone());
+            drop(known);
+            let _ = app.emit(
+                CYCLE_DATA_CHANGED_EVENT,
+                CycleDataChanged {
+                    cycle_id: cycle_id.clone(),
+                    revision,
+                },
+            );
+        })
+        .map_err(|e| AppError::internal(format!("Failed to create cycle watcher: {e}")))?;
+
+        // Watch the folder, not the file: atomic saves replace the file through a rename.
+        watcher
+            .watch(Path::new(&cycle.folder_path), RecursiveMode::NonRecursive)
+            .map_err(|e| AppError::io(&cycle.folder_path, format!("Failed to watch cycle folder: {e}")))?;
+        watchers.insert(cycle.id.clone(), watcher);
+        Ok(())
+    }
+
+    /// Stops watching every folder, e.g. once the index was replaced and may point elsewhere.
+    pub fn unwatch_all(&self) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            watchers.clear();
+        }
+        if let Ok(mut known) = self.known_revisions.lock() {
+            known.clear();
+        }
+    }
+
+    pub fn unwatch(&self, cycle_id: &str) {
+        if let Ok(mut watchers) = self.watchers.lock() {
+            watchers.remove(cycle_id);
+        }
+        if let Ok(mut known) = self.known_revisions.lock() {
+            known.remove(cycle_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_file() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("cycle-watch-test-{:x}", rand::random::<u64>()));
+        fs::create_dir_all(&folder).unwrap();
+        let file = folder.join("cycle_data.json");
+        fs::write(&file, "{}").unwrap();
+        file
+    }
+
+    fn save(
+        watchers: &CycleWatchers,
+        file: &Path,
+        expected: Option<String>,
+        contents: &str,
+    ) -> Result<Option<String>, AppError> {
+        let write = || fs::write(file, contents).map_err(|e| AppError::io(file, e.to_string()));
+        watchers.guarded_write("cycle_a", expected, || file_revision(file), write)
+    }
+
+    #[test]
+    fn a_matching_revision_writes_and_returns_the_new_one() {
+        let file = temp_file();
+        let watchers = CycleWatchers::default();
+
+        let revision = save(&watchers, &file, file_revision(&file), r#"{"n":1}"#).unwrap();
+        assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"n":1}"#);
+        assert_eq!(revision, file_revision(&file));
+        assert_eq!(watchers.known_revisions.lock().unwrap().get("cycle_a"), Some(&revision));
+        let _ = fs::remove_dir_all(file.parent().unwrap());
+    }
+
+    #[test]
+    fn a_stale_revision_is_rejected_without_writing() {
+        let file = temp_file();
+        let watchers = CycleWatchers::default();
+        let loaded = file_revision(&file);
+        fs::write(&file, r#"{"edited":"elsewhere"}"#).unwrap();
+
+        let error = save(&watchers, &file, loaded, r#"{"n":1}"#).unwrap_err();
+        assert_eq!((error.code, error.cycle_id.as_deref()), (ErrorCode::ExternalChange, Some("cycle_a")));
+        assert_eq!(fs::read_to_string(&file).unwrap(), r#"{"edited":"elsewhere"}"#);
+        assert!(watchers.known_revisions.lock().unwrap().is_empty());
+
+        // Without an expected revision the app's own writes always go through.
+        assert!(save(&watchers, &file, None, r#"{"n":2}"#).is_ok());
+        let _ = fs::remove_dir_all(file.parent().unwrap());
+    }
+}
@@ -0,0 +1,223 @@
+use crate::atomic_file::{read_with_backup, write_json_atomic};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const WINDOW_STATE_FILE: &str = "window_state.json";
+
+/// How much of the top edge of a window (where the title bar is) has to be on a monitor, in
+/// physical pixels, for the window to be reachable there.
+const MIN_VISIBLE_WIDTH: u32 = 120;
+const MIN_VISIBLE_HEIGHT: u32 = 40;
+
+/// Where the window was in one mode: its outer position and inner size in physical pixels, as
+/// the window reports them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    /// Name of the monitor the window was on, to put it back on the same one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+}
+
+/// The usable area of a monitor (without task bars and docks) in physical pixels.
+#[derive(Debug, Clone)]
+pub struct MonitorArea {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowGeometry {
+    fn title_bar_visible_on(&self, area: &MonitorArea) -> bool {
+        let left = i64::from(self.x).max(i64::from(area.x));
+        let right = (i64::from(self.x) + i64::from(self.width)).min(i64::from(area.x) + i64::from(area.width));
+        let top = i64::from(self.y);
+        let bottom = top + i64::from(MIN_VISIBLE_HEIGHT.min(self.height));
+        right - left >= i64::from(MIN_VISIBLE_WIDTH.min(self.width))
+            && top >= i64::from(area.y)
+            && bottom <= i64::from(area.y) + i64::from(area.height)
+    }
+
+    /// The geometry moved onto a visible monitor. A window whose title bar is on any monitor is
+    /// left alone; otherwise it goes back inside its own monitor if that is still connected, or is
+    /// centered on the first one (the primary). Sizes shrink to fit the monitor.
+    pub fn clamped_to(&self, monitors: &[MonitorArea]) -> WindowGeometry {
+        if monitors.is_empty() || monitors.iter().any(|area| self.title_bar_visible_on(area)) {
+            return self.clone();
+        }
+        let same_monitor = self
+            .monitor
+            .as_ref()
+            .and_then(|name| monitors.iter().find(|area| area.name.as_ref() == Some(name)));
+        let area = same_monitor.unwrap_or(&monitors[0]);
+        let width = self.width.min(area.width);
+        let height = self.height.min(area.height);
+        let max_x = area.x + (area.width - width) as i32;
+        let max_y = area.y + (area.height - height) as i32;
+        let (x, y) = if same_monitor.is_some() {
+            (self.x.clamp(area.x, max_x), self.y.clamp(area.y, max_y))
+        } else {
+            (area.x + ((area.width - width) / 2) as i32, area.y + ((area.height - height) / 2) as i32)
+        };
+        WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+            maximized: self.maximized,
+            monitor: area.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowState {
+    /// Keyed by window mode (`normal`, `postIt`, `calendar`).
+    #[serde(default)]
+    modes: BTreeMap<String, WindowGeometry>,
+}
+
+/// Window geometry per mode. Moves and resizes are only remembered in memory; `save` writes them
+/// to `window_state.json` when the window is hidden, switches mode or the app quits.
+pub struct WindowStateStore {
+    path: PathBuf,
+    current: Mutex<WindowState>,
+}
+
+impl WindowStateStore {
+    /// Loads the saved geometry in `root`. A missing or unreadable file starts from nothing, so
+    /// the window opens at its default size.
+    pub fn open(root: &Path) -> Self {
+        let store = Self {
+            path: root.join(WINDOW_STATE_FILE),
+            current: Mutex::new(WindowState::default()),
+        };
+        store.reload();
+        store
+    }
+
+    /// Reads the file again, e.g. after it was copied in from another data folder. Geometry only
+    /// remembered in memory is dropped, so it cannot overwrite the copied file on the next save.
+    pub fn reload(&self) {
+        let loaded = read_with_backup(&self.path, "window state", |raw| {
+            serde_json::from_str::<WindowState>(raw)
+                .map_err(|e| AppError::corrupt(format!("parse window state error: {e}")))
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+        if let Ok(mut current) = self.current.lock() {
+            *current = loaded;
+        }
+    }
+
+    pub fn get(&self, mode: &str) -> Option<WindowGeometry> {
+        self.current.lock().ok()?.modes.get(mode).cloned()
+    }
+
+    pub fn remember(&self, mode: &str, geometry: WindowGeometry) {
+        if let Ok(mut current) = self.current.lock() {
+            current.modes.insert(mode.to_string(), geometry);
+        }
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let state = self
+            .current
+            .lock()
+            .map_err(|_| AppError::internal("Failed to read window state."))?
+            .clone();
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| AppError::io(dir, format!("create app dir error: {e}")))?;
+        }
+        write_json_atomic(&self.path, &state, "window state")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_string()),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// A 1920x1040 primary monitor and a 1280x1024 one to its right.
+    fn monitors() -> Vec<MonitorArea> {
+        vec![monitor("A", 0, 0, 1920, 1040), monitor("B", 1920, 0, 1280, 1024)]
+    }
+
+    fn window(x: i32, y: i32, width: u32, height: u32, monitor: &str) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width,
+            height,
+            maximized: false,
+            monitor: Some(monitor.to_string()),
+        }
+    }
+
+    #[test]
+    fn a_visible_window_is_left_alone() {
+        let geometry = window(100, 100, 800, 600, "A");
+        assert_eq!(geometry.clamped_to(&monitors()), geometry);
+    }
+
+    #[test]
+    fn a_window_across_two_monitors_counts_as_visible() {
+        let geometry = window(1700, 200, 800, 600, "A");
+        assert_eq!(geometry.clamped_to(&monitors()), geometry);
+    }
+
+    #[test]
+    fn an_offscreen_window_goes_back_inside_its_monitor() {
+        let clamped = window(3100, 900, 800, 600, "B").clamped_to(&monitors());
+        assert_eq!(clamped, window(2400, 424, 800, 600, "B"));
+    }
+
+    #[test]
+    fn a_title_bar_above_the_top_edge_is_moved_down() {
+        let clamped = window(300, -200, 800, 600, "A").clamped_to(&monitors());
+        assert_eq!(clamped, window(300, 0, 800, 600, "A"));
+    }
+
+    #[test]
+    fn a_window_on_a_disconnected_monitor_is_centered_on_the_primary_one() {
+        let mut geometry = window(5000, 100, 2400, 600, "C");
+        geometry.maximized = true;
+        let clamped = geometry.clamped_to(&monitors());
+        assert_eq!(
+            clamped,
+            WindowGeometry {
+                maximized: true,
+                ..window(0, 220, 1920, 600, "A")
+            }
+        );
+    }
+
+    #[test]
+    fn without_monitors_nothing_changes() {
+        let geometry = window(-5000, -5000, 800, 600, "A");
+        assert_eq!(geometry.clamped_to(&[]), geometry);
+    }
+}
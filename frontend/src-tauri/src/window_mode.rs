@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+
+pub const WINDOW_MODE_CHANGED_EVENT: &str = "window-mode-changed";
+
+const DEFAULT_WINDOW_WIDTH: f64 = 1320.0;
+const DEFAULT_WINDOW_HEIGHT: f64 = 860.0;
+const POST_IT_WINDOW_WIDTH: f64 = 390.0;
+const POST_IT_WINDOW_HEIGHT: f64 = 560.0;
+const CALENDAR_WINDOW_WIDTH: f64 = 1080.0;
+const CALENDAR_WINDOW_HEIGHT: f64 = 760.0;
+
+/// Layout of the main window. Each mode remembers its own geometry; everything else a mode
+/// changes about the window is described here so `set_window_mode` handles every mode the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowMode {
+    #[default]
+    Normal,
+    PostIt,
+    Calendar,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [WindowMode::Normal, WindowMode::PostIt, WindowMode::Calendar];
+
+    /// The mode saved as `key`, or `None` for a name this version does not know.
+    pub fn from_key(key: &str) -> Option<WindowMode> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    /// Name of the mode in saved files, the same as its serialized form.
+    pub fn key(self) -> &'static str {
+        match self {
+            WindowMode::Normal => "normal",
+            WindowMode::PostIt => "postIt",
+            WindowMode::Calendar => "calendar",
+        }
+    }
+
+    /// Logical size the window takes the first time it enters the mode.
+    pub fn default_size(self) -> (f64, f64) {
+        match self {
+            WindowMode::Normal => (DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT),
+            WindowMode::PostIt => (POST_IT_WINDOW_WIDTH, POST_IT_WINDOW_HEIGHT),
+            WindowMode::Calendar => (CALENDAR_WINDOW_WIDTH, CALENDAR_WINDOW_HEIGHT),
+        }
+    }
+
+    /// Whether the mode keeps the window above others regardless of the always-on-top setting.
+    pub fn forces_always_on_top(self) -> bool {
+        self == WindowMode::PostIt
+    }
+
+    /// Whether the window is kept above others in this mode with the always-on-top setting at
+    /// `setting`.
+    pub fn effective_always_on_top(self, setting: bool) -> bool {
+        setting || self.forces_always_on_top()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_post_it_mode_overrides_the_always_on_top_setting() {
+        for mode in [WindowMode::Normal, WindowMode::Calendar] {
+            assert!(mode.effective_always_on_top(true));
+            assert!(!mode.effective_always_on_top(false));
+        }
+        assert!(WindowMode::PostIt.effective_always_on_top(true));
+        assert!(WindowMode::PostIt.effective_always_on_top(false));
+    }
+
+    #[test]
+    fn keys_round_trip_and_unknown_ones_are_rejected() {
+        for mode in WindowMode::ALL {
+            assert_eq!(WindowMode::from_key(mode.key()), Some(mode));
+            assert_eq!(serde_json::to_value(mode).unwrap(), mode.key());
+        }
+        assert_eq!(WindowMode::from_key("postit"), None);
+        assert_eq!(WindowMode::from_key(""), None);
+    }
+}
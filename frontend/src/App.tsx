@@ -8,29 +8,38 @@ import { TodoTab } from './components/TodoTab';
 import { AppIndex, CycleData, Goal, Task, Work, WorkStatus } from './types/models';
 import { uid } from './utils/model';
 import {
+  CycleMutation,
+  DesktopCommandError,
+  DesktopLocale,
+  DesktopWindowMode,
   closeDesktopWindow,
   createCycle,
   getDesktopAlwaysOnTopState,
-  getDesktopCalendarModeState,
+  getDesktopSettings,
   getDesktopWindowOpacity,
-  getDesktopPostItModeState,
+  getDesktopWindowMode,
   importCycle,
   isDesktopRuntime,
   loadCycleData,
   loadIndex,
   minimizeDesktopWindow,
+  mutateDesktopCycle,
+  onDesktopCycleDataChanged,
+  onDesktopSettingsChanged,
+  onDesktopWindowModeChanged,
   pickFolder,
   saveCycleData,
   selectCycle,
   startDesktopWindowDragging,
+  setDesktopLocale,
+  setDesktopWindowMode,
   setDesktopWindowOpacity,
   toggleDesktopAlwaysOnTop,
-  toggleDesktopCalendarMode,
-  toggleDesktopPostItMode,
   toggleMaximizeDesktopWindow
 } from './utils/storage';
 
 type AppTab = 'goals' | 'calendar' | 'todo';
+type LocaleChoice = DesktopLocale | 'system';
 
 const WINDOW_OPACITY_MIN = 0.5;
 const WINDOW_OPACITY_MAX = 1;
@@ -83,11 +92,11 @@ export default function App() {
   const [cycleParentDir, setCycleParentDir] = useState('');
   const [loading, setLoading] = useState(true);
   const [alwaysOnTop, setAlwaysOnTop] = useState(false);
-  const [postItMode, setPostItMode] = useState(false);
-  const [calendarMode, setCalendarMode] = useState(false);
+  const [windowMode, setWindowMode] = useState<DesktopWindowMode>('normal');
   const [windowOpacity, setWindowOpacity] = useState(1);
   const [opacityPanelOpen, setOpacityPanelOpen] = useState(false);
   const opacityPanelRef = useRef<HTMLDivElement>(null);
+  const [localeChoice, setLocaleChoice] = useState<LocaleChoice>('system');
   const isDesktop = isDesktopRuntime();
 
   const selectedCycleId = index.selectedCycleId;
@@ -103,7 +112,12 @@ export default function App() {
 
   const showError = (error: unknown, fallbackMessage: string) => {
     const message = error instanceof Error ? error.message : fallbackMessage;
-    window.alert(message);
+    const lines = [message];
+    if (error instanceof DesktopCommandError) {
+      if (error.detail && error.detail !== message) lines.push(error.detail);
+      if (error.path && !lines.some((line) => line.includes(error.path as string))) lines.push(error.path);
+    }
+    window.alert(lines.join('\n'));
   };
 
   const loadAndSetCycle = async (cycleId: string) => {
@@ -111,6 +125,18 @@ export default function App() {
     setCycleData(normalizeCycleData(cycleId, data));
   };
 
+  const offerReloadAfterExternalChange = async (cycleId: string, error: unknown) => {
+    if (!(error instanceof DesktopCommandError) || error.code !== 'EXTERNAL_CHANGE') throw error;
+    if (window.confirm(`${error.message}\n\nReload the Cycle now? Your last change will be discarded.`)) {
+      await loadAndSetCycle(cycleId);
+    }
+  };
+
+  const recoverFromFailedSave = async (cycleId: string, previous: CycleData, error: unknown) => {
+    setCycleData(previous);
+    await runWithErrorAlert('Failed to save Cycle.', () => offerReloadAfterExternalChange(cycleId, error));
+  };
+
   useEffect(() => {
     document.documentElement.dataset.theme = theme;
     window.localStorage.setItem('theme', theme);
@@ -120,26 +146,88 @@ export default function App() {
     if (!isDesktop) return;
     const syncDesktopWindowState = async () => {
       try {
-        const [pinState, postItState, calendarState, opacityState] = await Promise.all([
+        const [pinState, modeState, opacityState] = await Promise.all([
           getDesktopAlwaysOnTopState(),
-          getDesktopPostItModeState(),
-          getDesktopCalendarModeState(),
+          getDesktopWindowMode(),
           getDesktopWindowOpacity()
         ]);
         setAlwaysOnTop(pinState);
-        setPostItMode(postItState);
-        setCalendarMode(calendarState);
+        setWindowMode(modeState);
         setWindowOpacity(clampWindowOpacity(opacityState));
       } catch {
         setAlwaysOnTop(false);
-        setPostItMode(false);
-        setCalendarMode(false);
+        setWindowMode('normal');
         setWindowOpacity(WINDOW_OPACITY_MAX);
       }
     };
     void syncDesktopWindowState();
   }, [isDesktop]);
 
+  useEffect(() => {
+    if (!isDesktop) return;
+    getDesktopSettings()
+      .then((settings) => setLocaleChoice(settings?.locale ?? 'system'))
+      .catch(() => setLocaleChoice('system'));
+  }, [isDesktop]);
+
+  useEffect(() => {
+    if (!isDesktop) return;
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    onDesktopSettingsChanged((settings) => {
+      setLocaleChoice(settings.locale ?? 'system');
+      setWindowOpacity(clampWindowOpacity(settings.windowOpacity));
+      getDesktopAlwaysOnTopState().then(setAlwaysOnTop).catch(() => undefined);
+    })
+      .then((stop) => {
+        if (disposed) stop();
+        else unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [isDesktop]);
+
+  useEffect(() => {
+    if (!isDesktop) return;
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    onDesktopWindowModeChanged((mode) => {
+      setWindowMode(mode);
+      getDesktopAlwaysOnTopState().then(setAlwaysOnTop).catch(() => undefined);
+    })
+      .then((stop) => {
+        if (disposed) stop();
+        else unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [isDesktop]);
+
+  useEffect(() => {
+    if (!isDesktop || !selectedCycleId) return;
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    onDesktopCycleDataChanged((change) => {
+      if (change.cycleId !== selectedCycleId) return;
+      loadAndSetCycle(change.cycleId).catch((error) => showError(error, 'Failed to reload Cycle.'));
+    })
+      .then((stop) => {
+        if (disposed) stop();
+        else unlisten = stop;
+      })
+      .catch(() => undefined);
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
+  }, [isDesktop, selectedCycleId]);
+
   useEffect(() => {
     if (!opacityPanelOpen) return;
     const onDocMouseDown = (event: MouseEvent) => {
@@ -221,110 +309,137 @@ export default function App() {
   const persistCycleData = async (next: CycleData) => {
     const cycleId = index.selectedCycleId;
     if (!cycleId) return;
+    const previous = cycleData;
     setCycleData(next);
-    await saveCycleData(cycleId, next);
+    try {
+      await saveCycleData(cycleId, next);
+    } catch (error) {
+      await recoverFromFailedSave(cycleId, previous, error);
+    }
   };
 
-  const applyCycleDataUpdate = async (updater: (data: CycleData) => CycleData) => {
+  const applyCycleDataUpdate = async (updater: (data: CycleData) => CycleData, mutation?: CycleMutation) => {
+    const cycleId = index.selectedCycleId;
+    if (cycleId && mutation && isDesktop) {
+      const previous = cycleData;
+      setCycleData(updater(previous));
+      try {
+        await mutateDesktopCycle(cycleId, mutation);
+      } catch (error) {
+        await recoverFromFailedSave(cycleId, previous, error);
+      }
+      return;
+    }
     await persistCycleData(updater(cycleData));
   };
 
   const createGoal = async (payload: { title: string; startDate?: string; endDate?: string }) => {
     if (!selectedCycleId) return;
     const goal: Goal = { id: uid('goal'), cycleId: selectedCycleId, ...payload };
-    await applyCycleDataUpdate((data) => ({ ...data, goals: [...data.goals, goal] }));
+    await applyCycleDataUpdate((data) => ({ ...data, goals: [...data.goals, goal] }), { command: 'upsert_goal', args: { goal } });
   };
 
   const createWork = async (payload: { title: string; goalId?: string; startDate?: string; endDate?: string; body?: string; status?: WorkStatus }) => {
     if (!selectedCycleId) return;
     const work: Work = { id: uid('work'), cycleId: selectedCycleId, status: payload.status ?? 'NOT_STARTED', ...payload };
-    await applyCycleDataUpdate((data) => ({ ...data, works: [work, ...data.works] }));
+    await applyCycleDataUpdate((data) => ({ ...data, works: [work, ...data.works] }), { command: 'upsert_work', args: { work } });
   };
 
   const createTask = async (payload: { title: string; workId: string; dueDate?: string }) => {
     if (!selectedCycleId) return;
     const task: Task = { id: uid('task'), cycleId: selectedCycleId, done: false, ...payload };
-    await applyCycleDataUpdate((data) => ({ ...data, tasks: [...data.tasks, task] }));
+    await applyCycleDataUpdate((data) => ({ ...data, tasks: [...data.tasks, task] }), { command: 'upsert_task', args: { task } });
   };
 
   const changeWorkStatus = async (workId: string, status: WorkStatus) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      works: data.works.map((work) => (work.id === workId ? { ...work, status } : work))
-    }));
+    await updateWork(workId, { status });
   };
 
   const updateWork = async (workId: string, patch: Partial<Pick<Work, 'title' | 'status' | 'startDate' | 'endDate' | 'body'>>) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      works: data.works.map((work) => (work.id === workId ? { ...work, ...patch } : work))
-    }));
+    const existing = cycleData.works.find((work) => work.id === workId);
+    await applyCycleDataUpdate(
+      (data) => ({
+        ...data,
+        works: data.works.map((work) => (work.id === workId ? { ...work, ...patch } : work))
+      }),
+      existing && { command: 'upsert_work', args: { work: { ...existing, ...patch } } }
+    );
   };
 
   const toggleTaskDone = async (taskId: string) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      tasks: data.tasks.map((task) => (task.id === taskId ? { ...task, done: !task.done } : task))
-    }));
+    await applyCycleDataUpdate(
+      (data) => ({
+        ...data,
+        tasks: data.tasks.map((task) => (task.id === taskId ? { ...task, done: !task.done } : task))
+      }),
+      { command: 'toggle_task', args: { taskId } }
+    );
   };
 
   const updateTask = async (taskId: string, patch: Partial<Pick<Task, 'title' | 'dueDate'>>) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      tasks: data.tasks.map((task) => (task.id === taskId ? { ...task, ...patch } : task))
-    }));
+    const existing = cycleData.tasks.find((task) => task.id === taskId);
+    await applyCycleDataUpdate(
+      (data) => ({
+        ...data,
+        tasks: data.tasks.map((task) => (task.id === taskId ? { ...task, ...patch } : task))
+      }),
+      existing && { command: 'upsert_task', args: { task: { ...existing, ...patch } } }
+    );
   };
 
   const deleteTask = async (taskId: string) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      tasks: data.tasks.filter((task) => task.id !== taskId)
-    }));
+    await applyCycleDataUpdate(
+      (data) => ({
+        ...data,
+        tasks: data.tasks.filter((task) => task.id !== taskId)
+      }),
+      { command: 'delete_task', args: { taskId } }
+    );
   };
 
   const deleteWork = async (workId: string) => {
-    await applyCycleDataUpdate((data) => ({
-      ...data,
-      works: data.works.filter((work) => work.id !== workId),
-      tasks: data.tasks.filter((task) => task.workId !== workId)
-    }));
+    await applyCycleDataUpdate(
+      (data) => ({
+        ...data,
+        works: data.works.filter((work) => work.id !== workId),
+        tasks: data.tasks.filter((task) => task.workId !== workId)
+      }),
+      { command: 'delete_work', args: { workId } }
+    );
   };
 
   const deleteGoal = async (goalId: string) => {
-    await applyCycleDataUpdate((data) => {
-      const childWorkIds = new Set(data.works.filter((work) => work.goalId === goalId).map((work) => work.id));
-      return {
-        ...data,
-        goals: data.goals.filter((goal) => goal.id !== goalId),
-        works: data.works.filter((work) => work.goalId !== goalId),
-        tasks: data.tasks.filter((task) => !childWorkIds.has(task.workId))
-      };
-    });
+    await applyCycleDataUpdate(
+      (data) => {
+        const childWorkIds = new Set(data.works.filter((work) => work.goalId === goalId).map((work) => work.id));
+        return {
+          ...data,
+          goals: data.goals.filter((goal) => goal.id !== goalId),
+          works: data.works.filter((work) => work.goalId !== goalId),
+          tasks: data.tasks.filter((task) => !childWorkIds.has(task.workId))
+        };
+      },
+      { command: 'delete_goal', args: { goalId } }
+    );
+  };
+
+  const switchWindowMode = async (mode: DesktopWindowMode) => {
+    const next = await setDesktopWindowMode(windowMode === mode ? 'normal' : mode);
+    setWindowMode(next);
+    setAlwaysOnTop(await getDesktopAlwaysOnTopState());
+    return next;
   };
 
   const togglePostItWindowMode = async () => {
     await runWithErrorAlert('Failed to toggle post-it mode.', async () => {
-      const nextPostItMode = await toggleDesktopPostItMode();
-      setPostItMode(nextPostItMode);
-      if (nextPostItMode) {
-        setCalendarMode(false);
-      }
-
-      if (nextPostItMode) {
-        setAlwaysOnTop(true);
-      } else {
-        const pinState = await getDesktopAlwaysOnTopState();
-        setAlwaysOnTop(pinState);
-      }
+      await switchWindowMode('postIt');
     });
   };
 
   const toggleCalendarWindowMode = async () => {
     await runWithErrorAlert('Failed to toggle calendar mode.', async () => {
-      const nextCalendarMode = await toggleDesktopCalendarMode();
-      setCalendarMode(nextCalendarMode);
-      if (nextCalendarMode) {
-        setPostItMode(false);
+      const next = await switchWindowMode('calendar');
+      if (next === 'calendar') {
         setTab('calendar');
       }
     });
@@ -337,14 +452,21 @@ export default function App() {
     });
   };
 
+  const changeLocale = async (choice: LocaleChoice) => {
+    await runWithErrorAlert('Failed to change language.', async () => {
+      await setDesktopLocale(choice === 'system' ? null : choice);
+      setLocaleChoice(choice);
+    });
+  };
+
   const hasCycle = index.cycles.length > 0 && Boolean(selectedCycleId);
 
   return (
     <div className={`app-shell ${isDesktop ? 'desktop-shell' : ''}`}>
       {isDesktop && (
         <DesktopWindowChrome
-          postItMode={postItMode}
-          calendarMode={calendarMode}
+          postItMode={windowMode === 'postIt'}
+          calendarMode={windowMode === 'calendar'}
           alwaysOnTop={alwaysOnTop}
           opacityPanelOpen={opacityPanelOpen}
           windowOpacityPercent={Math.round(windowOpacity * 100)}
@@ -364,15 +486,30 @@ export default function App() {
       <header className="top-bar">
         <div className="top-bar-head">
           <h1>Cycle</h1>
-          <button
-            type="button"
-            className="theme-toggle"
-            onClick={() => setTheme((prev) => (prev === 'light' ? 'dark' : 'light'))}
-            aria-label="Toggle theme"
-            title="Toggle light/dark mode"
-          >
-            {theme === 'light' ? 'Dark' : 'Light'}
-          </button>
+          <div className="top-bar-actions">
+            <button
+              type="button"
+              className="theme-toggle"
+              onClick={() => setTheme((prev) => (prev === 'light' ? 'dark' : 'light'))}
+              aria-label="Toggle theme"
+              title="Toggle light/dark mode"
+            >
+              {theme === 'light' ? 'Dark' : 'Light'}
+            </button>
+            {isDesktop && (
+              <select
+                className="locale-select"
+                value={localeChoice}
+                onChange={(event) => void changeLocale(event.target.value as LocaleChoice)}
+                aria-label="Language"
+                title="Language of desktop messages and the tray menu"
+              >
+                <option value="system">System</option>
+                <option value="en">English</option>
+                <option value="ko">한국어</option>
+              </select>
+            )}
+          </div>
         </div>
         <CycleSelector
           cycles={index.cycles}
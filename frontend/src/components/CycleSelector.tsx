@@ -24,6 +24,7 @@ export function CycleSelector({
   onImportCycle
 }: CycleSelectorProps) {
   const [cycleName, setCycleName] = useState('');
+  const visibleCycles = cycles.filter((cycle) => !cycle.archived || cycle.id === selectedCycleId);
 
   const submit = (event: FormEvent) => {
     event.preventDefault();
@@ -42,9 +43,9 @@ export function CycleSelector({
         className="cycle-select"
         value={selectedCycleId ?? ''}
         onChange={onSelect}
-        options={cycles.length === 0
+        options={visibleCycles.length === 0
           ? [{ value: '', label: 'Create a Cycle first' }]
-          : cycles.map((cycle) => ({ value: cycle.id, label: cycle.name }))}
+          : visibleCycles.map((cycle) => ({ value: cycle.id, label: cycle.name }))}
       />
       <form onSubmit={submit} className="inline-form">
         <input
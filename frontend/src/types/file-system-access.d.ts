@@ -14,6 +14,10 @@ declare global {
   interface Window {
     __TAURI_INTERNALS__?: {
       invoke: <T = unknown>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
+      transformCallback: <T = unknown>(callback: (message: T) => void, once?: boolean) => number;
+    };
+    __TAURI_EVENT_PLUGIN_INTERNALS__?: {
+      unregisterListener: (event: string, eventId: number) => void;
     };
 
     showDirectoryPicker(options?: {
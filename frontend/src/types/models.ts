@@ -1,12 +1,18 @@
 export type WorkStatus = 'NOT_STARTED' | 'IN_PROGRESS' | 'DONE';
 export type GoalStatus = 'NOT_STARTED' | 'IN_PROGRESS' | 'DONE';
 
+export interface EntityOrigin {
+  cycleId: string;
+  id: string;
+}
+
 export interface Goal {
   id: string;
   cycleId: string;
   title: string;
   startDate?: string;
   endDate?: string;
+  rolledOverFrom?: EntityOrigin;
 }
 
 export interface Work {
@@ -18,6 +24,7 @@ export interface Work {
   startDate?: string;
   endDate?: string;
   body?: string;
+  rolledOverFrom?: EntityOrigin;
 }
 
 export interface Task {
@@ -27,6 +34,7 @@ export interface Task {
   title: string;
   done: boolean;
   dueDate?: string;
+  rolledOverFrom?: EntityOrigin;
 }
 
 export interface Cycle {
@@ -34,14 +42,20 @@ export interface Cycle {
   name: string;
   createdAt: string;
   folderPath?: string;
+  archived?: boolean;
+  missing?: boolean;
+  closedAt?: string;
+  storage?: 'json' | 'sqlite';
 }
 
 export interface AppIndex {
+  schemaVersion?: number;
   cycles: Cycle[];
   selectedCycleId?: string;
 }
 
 export interface CycleData {
+  schemaVersion?: number;
   id: string;
   name: string;
   createdAt: string;
@@ -1,4 +1,5 @@
 import { AppIndex, CycleData } from '../types/models';
+import { uid } from './model';
 
 const INDEX_KEY = 'cycle_planner_index_v3';
 const CYCLE_FILE = 'cycle_data.json';
@@ -11,9 +12,85 @@ const emptyIndex: AppIndex = {
 };
 
 let selectedParentHandle: FileSystemDirectoryHandle | null = null;
+const desktopRevisions = new Map<string, string | null>();
 
 type TauriInvoke = <T = unknown>(cmd: string, args?: Record<string, unknown>) => Promise<T>;
 
+export type DesktopErrorCode =
+  | 'CYCLE_NOT_FOUND'
+  | 'FOLDER_MISSING'
+  | 'INVALID_FOLDER'
+  | 'CYCLE_DATA_NOT_FOUND'
+  | 'CORRUPT_DATA'
+  | 'NEWER_SCHEMA'
+  | 'IO'
+  | 'EXTERNAL_CHANGE'
+  | 'NOT_FOUND'
+  | 'INVALID_INPUT'
+  | 'WINDOW'
+  | 'INTERNAL';
+
+type DesktopErrorPayload = {
+  code: DesktopErrorCode;
+  message: string;
+  detail?: string;
+  path?: string;
+  cycleId?: string;
+};
+
+export type DesktopLocale = 'en' | 'ko';
+
+export type DesktopWindowMode = 'normal' | 'postIt' | 'calendar';
+
+export type DesktopSettings = {
+  schemaVersion: number;
+  locale: DesktopLocale | null;
+  windowOpacity: number;
+  alwaysOnTop: boolean;
+  windowMode: DesktopWindowMode;
+  reminders: {
+    enabled: boolean;
+    leadDays: number;
+  };
+};
+
+export type DesktopSettingsPatch = Partial<Omit<DesktopSettings, 'schemaVersion' | 'reminders'>> & {
+  reminders?: Partial<DesktopSettings['reminders']>;
+};
+
+export type DesktopCycleDataChange = {
+  cycleId: string;
+  revision: string | null;
+};
+
+export type DesktopDataDir = {
+  path: string;
+  source: 'commandLine' | 'environment' | 'portable' | 'default';
+  defaultPath: string;
+};
+
+export class DesktopCommandError extends Error {
+  readonly code: DesktopErrorCode;
+  readonly detail?: string;
+  readonly path?: string;
+  readonly cycleId?: string;
+
+  constructor(payload: DesktopErrorPayload) {
+    super(payload.message);
+    this.name = 'DesktopCommandError';
+    this.code = payload.code;
+    this.detail = payload.detail;
+    this.path = payload.path;
+    this.cycleId = payload.cycleId;
+  }
+}
+
+function isDesktopErrorPayload(value: unknown): value is DesktopErrorPayload {
+  if (typeof value !== 'object' || value === null) return false;
+  const candidate = value as Partial<DesktopErrorPayload>;
+  return typeof candidate.code === 'string' && typeof candidate.message === 'string';
+}
+
 function getTauriInvoke(): TauriInvoke | null {
   if (typeof window === 'undefined') return null;
   const candidate = (window as Window & { __TAURI_INTERNALS__?: { invoke?: TauriInvoke } }).__TAURI_INTERNALS__?.invoke;
@@ -29,7 +106,14 @@ async function invokeDesktop<T>(cmd: string, args?: Record<string, unknown>): Pr
   if (!invoke) {
     throw new Error('Desktop runtime is not available.');
   }
-  return invoke<T>(cmd, args);
+  try {
+    return await invoke<T>(cmd, args);
+  } catch (error) {
+    if (isDesktopErrorPayload(error)) {
+      throw new DesktopCommandError(error);
+    }
+    throw typeof error === 'string' ? new Error(error) : error;
+  }
 }
 
 async function invokeDesktopOr<T>(fallback: T, cmd: string, args?: Record<string, unknown>): Promise<T> {
@@ -37,6 +121,26 @@ async function invokeDesktopOr<T>(fallback: T, cmd: string, args?: Record<string
   return invokeDesktop<T>(cmd, args);
 }
 
+type DesktopEvent<T> = {
+  event: string;
+  id: number;
+  payload: T;
+};
+
+async function listenDesktop<T>(event: string, handler: (payload: T) => void): Promise<() => void> {
+  const internals = typeof window === 'undefined' ? undefined : window.__TAURI_INTERNALS__;
+  if (!internals || typeof internals.transformCallback !== 'function') return () => {};
+  const eventId = await invokeDesktop<number>('plugin:event|listen', {
+    event,
+    target: { kind: 'Any' },
+    handler: internals.transformCallback<DesktopEvent<T>>((message) => handler(message.payload))
+  });
+  return () => {
+    window.__TAURI_EVENT_PLUGIN_INTERNALS__?.unregisterListener(event, eventId);
+    void invokeDesktop<void>('plugin:event|unlisten', { event, eventId }).catch(() => undefined);
+  };
+}
+
 function cycleKey(cycleId: string): string {
   return `cycle_planner_cycle_${cycleId}`;
 }
@@ -45,15 +149,34 @@ function supportsFsApi(): boolean {
   return typeof window !== 'undefined' && !isTauriDesktop() && 'showDirectoryPicker' in window;
 }
 
-function uid(prefix: string): string {
-  return `${prefix}_${crypto.randomUUID()}`;
-}
+const MAX_FOLDER_NAME_CHARS = 60;
 
+// Same rules as sanitize_folder_name in the desktop backend.
 function sanitizeFolderName(name: string): string {
-  const cleaned = name.replace(/[^a-zA-Z0-9_-]/g, '_').replace(/^_+|_+$/g, '');
+  const replaced = Array.from(name.replace(/[^\p{Alphabetic}\p{N}_-]/gu, '_'))
+    .slice(0, MAX_FOLDER_NAME_CHARS)
+    .join('');
+  const cleaned = replaced.replace(/^_+|_+$/g, '');
   return cleaned || 'cycle';
 }
 
+async function hasChildEntry(parent: FileSystemDirectoryHandle, name: string): Promise<boolean> {
+  try {
+    await parent.getDirectoryHandle(name);
+    return true;
+  } catch (error) {
+    return !(error instanceof DOMException && error.name === 'NotFoundError');
+  }
+}
+
+async function uniqueChildName(parent: FileSystemDirectoryHandle, base: string): Promise<string> {
+  let candidate = base;
+  for (let n = 2; await hasChildEntry(parent, candidate); n += 1) {
+    candidate = `${base}-${n}`;
+  }
+  return candidate;
+}
+
 function openHandleDb(): Promise<IDBDatabase> {
   return new Promise((resolve, reject) => {
     const request = indexedDB.open(HANDLE_DB, 1);
@@ -171,7 +294,7 @@ export async function createCycle(name: string, parentDir: string): Promise<AppI
   const index = await loadIndex();
   const cycleId = uid('cycle');
   const suffix = cycleId.slice(-6);
-  const folderName = `${sanitizeFolderName(name)}_${suffix}`;
+  const folderName = await uniqueChildName(selectedParentHandle, `${sanitizeFolderName(name)}_${suffix}`);
   const cycleFolder = await selectedParentHandle.getDirectoryHandle(folderName, { create: true });
 
   const createdAt = new Date().toISOString();
@@ -253,7 +376,9 @@ export async function importCycle(folderPath: string): Promise<AppIndex> {
 
 export async function loadCycleData(cycleId: string): Promise<CycleData> {
   if (isTauriDesktop()) {
-    return invokeDesktop<CycleData>('load_cycle_data', { cycleId });
+    const data = await invokeDesktop<CycleData>('load_cycle_data', { cycleId });
+    desktopRevisions.set(cycleId, await invokeDesktop<string | null>('get_cycle_revision', { cycleId }));
+    return data;
   }
 
   const index = await loadIndex();
@@ -292,7 +417,9 @@ export async function loadCycleData(cycleId: string): Promise<CycleData> {
 
 export async function saveCycleData(cycleId: string, data: CycleData): Promise<void> {
   if (isTauriDesktop()) {
-    await invokeDesktop<void>('save_cycle_data', { cycleId, data });
+    const expectedRevision = desktopRevisions.get(cycleId) ?? undefined;
+    const revision = await invokeDesktop<string | null>('save_cycle_data', { cycleId, data, expectedRevision });
+    desktopRevisions.set(cycleId, revision);
     return;
   }
 
@@ -308,10 +435,74 @@ export async function saveCycleData(cycleId: string, data: CycleData): Promise<v
   localStorage.setItem(cycleKey(cycleId), JSON.stringify(data));
 }
 
+export type CycleMutation = {
+  command:
+    | 'upsert_goal'
+    | 'upsert_work'
+    | 'upsert_task'
+    | 'delete_goal'
+    | 'delete_work'
+    | 'delete_task'
+    | 'toggle_task'
+    | 'move_work_to_goal';
+  args: Record<string, unknown>;
+};
+
+type DesktopMutationResult<T> = {
+  value: T;
+  revision: string | null;
+};
+
+export async function mutateDesktopCycle<T>(cycleId: string, mutation: CycleMutation): Promise<T> {
+  const expectedRevision = desktopRevisions.get(cycleId) ?? undefined;
+  const result = await invokeDesktop<DesktopMutationResult<T>>(mutation.command, {
+    cycleId,
+    ...mutation.args,
+    expectedRevision
+  });
+  desktopRevisions.set(cycleId, result.revision);
+  return result.value;
+}
+
+export async function onDesktopCycleDataChanged(handler: (change: DesktopCycleDataChange) => void): Promise<() => void> {
+  return listenDesktop<DesktopCycleDataChange>('cycle-data-changed', (change) => {
+    if (desktopRevisions.get(change.cycleId) === change.revision) return;
+    handler(change);
+  });
+}
+
 export function isDesktopRuntime(): boolean {
   return isTauriDesktop();
 }
 
+export async function getDesktopDataDir(): Promise<DesktopDataDir | null> {
+  return invokeDesktopOr<DesktopDataDir | null>(null, 'get_data_dir');
+}
+
+export async function migrateDesktopIndex(sourceDir?: string): Promise<AppIndex> {
+  return invokeDesktop<AppIndex>('migrate_index', { sourceDir: sourceDir ?? null });
+}
+
+export async function getDesktopSettings(): Promise<DesktopSettings | null> {
+  return invokeDesktopOr<DesktopSettings | null>(null, 'get_settings');
+}
+
+export async function updateDesktopSettings(patch: DesktopSettingsPatch): Promise<DesktopSettings> {
+  return invokeDesktop<DesktopSettings>('update_settings', { patch });
+}
+
+export async function onDesktopSettingsChanged(handler: (settings: DesktopSettings) => void): Promise<() => void> {
+  return listenDesktop<DesktopSettings>('settings-changed', handler);
+}
+
+export async function getDesktopLocale(): Promise<DesktopLocale> {
+  return invokeDesktopOr<DesktopLocale>('en', 'get_locale');
+}
+
+export async function setDesktopLocale(locale: DesktopLocale | null): Promise<DesktopLocale> {
+  return invokeDesktop<DesktopLocale>('set_locale', { locale });
+}
+
 export async function minimizeDesktopWindow(): Promise<void> {
   await invokeDesktopOr<void>(undefined, 'window_minimize');
 }
@@ -336,20 +527,16 @@ export async function toggleDesktopAlwaysOnTop(): Promise<boolean> {
   return invokeDesktopOr<boolean>(false, 'window_toggle_always_on_top');
 }
 
-export async function getDesktopPostItModeState(): Promise<boolean> {
-  return invokeDesktopOr<boolean>(false, 'window_is_post_it_mode');
-}
-
-export async function toggleDesktopPostItMode(): Promise<boolean> {
-  return invokeDesktopOr<boolean>(false, 'window_toggle_post_it_mode');
+export async function getDesktopWindowMode(): Promise<DesktopWindowMode> {
+  return invokeDesktopOr<DesktopWindowMode>('normal', 'get_window_mode');
 }
 
-export async function getDesktopCalendarModeState(): Promise<boolean> {
-  return invokeDesktopOr<boolean>(false, 'window_is_calendar_mode');
+export async function setDesktopWindowMode(mode: DesktopWindowMode): Promise<DesktopWindowMode> {
+  return invokeDesktopOr<DesktopWindowMode>('normal', 'set_window_mode', { mode });
 }
 
-export async function toggleDesktopCalendarMode(): Promise<boolean> {
-  return invokeDesktopOr<boolean>(false, 'window_toggle_calendar_mode');
+export async function onDesktopWindowModeChanged(handler: (mode: DesktopWindowMode) => void): Promise<() => void> {
+  return listenDesktop<DesktopWindowMode>('window-mode-changed', handler);
 }
 
 export async function getDesktopWindowOpacity(): Promise<number> {